@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{create_new_growths, growth::Growth, step_growths_serial, Config, WINDOW_SIZE},
+    palette::Palette,
+    smoothing::Smoothing,
+};
+use nannou::geom::Rect;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const SEED: u64 = 1;
+const DT: Duration = Duration::from_millis(16);
+const GROWTH_COUNTS: [u64; 4] = [5, 10, 20, 40];
+/// How many updates to run before measuring, so lines have a realistic number of points.
+const WARM_UP_STEPS: usize = 60;
+
+fn growths(num_growths: u64, config: &Config) -> Vec<Growth> {
+    let mut growths = create_new_growths(
+        Rect::from_w_h(WINDOW_SIZE, WINDOW_SIZE),
+        num_growths,
+        &Palette::default(),
+        &mut ChaCha8Rng::seed_from_u64(SEED),
+    );
+    for _ in 0..WARM_UP_STEPS {
+        step_growths_serial(&mut growths, DT, config);
+    }
+    growths
+}
+
+fn step_line(c: &mut Criterion) {
+    let config = Config::default();
+    let line = growths(2, &config)
+        .into_iter()
+        .flat_map(|g| g.lines)
+        .find(|l| !l.is_finished())
+        .expect("an unfinished line");
+
+    c.bench_function("Line::step_line", |b| {
+        b.iter_batched_ref(
+            || line.clone(),
+            |line| line.step_line(DT, &config),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn step_growth(c: &mut Criterion) {
+    let config = Config::default();
+    let mut group = c.benchmark_group("Growth::step_growth");
+
+    for num_growths in GROWTH_COUNTS {
+        let growth = growths(num_growths, &config).remove(0);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_growths),
+            &growth,
+            |b, growth| {
+                b.iter_batched_ref(
+                    || growth.clone(),
+                    |growth| growth.step_growth(DT, &config),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn new_growths(c: &mut Criterion) {
+    let palette = Palette::default();
+    let rect = Rect::from_w_h(WINDOW_SIZE, WINDOW_SIZE);
+    let mut group = c.benchmark_group("create_new_growths");
+
+    for num_growths in GROWTH_COUNTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_growths),
+            &num_growths,
+            |b, num_growths| {
+                let mut rng = ChaCha8Rng::seed_from_u64(SEED);
+                b.iter(|| create_new_growths(rect, *num_growths, &palette, &mut rng))
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Everything `Line::draw` does before handing points to nannou, for every line on the canvas.
+fn draw_points(c: &mut Criterion) {
+    let config = Config::default();
+    let mut group = c.benchmark_group("Line::draw_points");
+
+    for num_growths in GROWTH_COUNTS {
+        let growths = growths(num_growths, &config);
+        for (name, smoothing) in [
+            ("none", Smoothing::None),
+            ("chaikin", Smoothing::Chaikin { iterations: 2 }),
+            ("catmull-rom", Smoothing::CatmullRom { spacing: 4.0 }),
+        ] {
+            group.bench_with_input(
+                BenchmarkId::new(name, num_growths),
+                &growths,
+                |b, growths| {
+                    b.iter(|| {
+                        growths
+                            .iter()
+                            .flat_map(|g| &g.lines)
+                            .map(|l| l.draw_points(4.0, &smoothing).len())
+                            .sum::<usize>()
+                    })
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step_line, step_growth, new_growths, draw_points);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{create_new_growths, step_growths, step_growths_serial, Config, WINDOW_SIZE},
+    palette::Palette,
+};
+use nannou::geom::Rect;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const SEED: u64 = 1;
+const DT: Duration = Duration::from_millis(16);
+
+/// Serial against parallel stepping of every line, for growth counts where the thread pool should
+/// start to pay off.
+fn stepping(c: &mut Criterion) {
+    let config = Config::default();
+    let palette = Palette::default();
+    let rect = Rect::from_w_h(WINDOW_SIZE, WINDOW_SIZE);
+    let mut group = c.benchmark_group("step_growths");
+
+    for num_growths in [20, 50, 80] {
+        let growths = create_new_growths(
+            rect,
+            num_growths,
+            &palette,
+            &mut ChaCha8Rng::seed_from_u64(SEED),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("serial", num_growths),
+            &growths,
+            |b, growths| {
+                b.iter_batched_ref(
+                    || growths.clone(),
+                    |growths| step_growths_serial(growths, DT, &config),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parallel", num_growths),
+            &growths,
+            |b, growths| {
+                b.iter_batched_ref(
+                    || growths.clone(),
+                    |growths| step_growths(growths, DT, &config),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, stepping);
+criterion_main!(benches);
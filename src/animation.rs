@@ -0,0 +1,207 @@
+use std::{f32::consts::PI, time::Duration};
+
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The shape of a value cycling over time.
+///
+/// Every curve is sampled at a fraction `t` of the way through a cycle and returns a value between
+/// 0 and 1. The easing curves go up over the first half of the cycle and back down over the second
+/// so they loop without jumping.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Curve {
+    /// Linear there and back, starting at the top.
+    Triangle,
+    Sine,
+    EaseInQuad,
+    EaseOutQuad,
+    EaseInOutQuad,
+    EaseInCubic,
+    EaseOutCubic,
+    EaseInOutCubic,
+    EaseInExpo,
+    EaseOutExpo,
+    EaseInOutExpo,
+    Smoothstep,
+    Square,
+    Sawtooth,
+    Bounce,
+    /// `(t, value)` pairs with `t` between 0 and 1, linearly interpolated and wrapping around.
+    Keyframes(Vec<(f32, f32)>),
+}
+
+impl Curve {
+    /// Every curve that doesn't need any extra information, in the order they're cycled through.
+    pub const PRESETS: [Curve; 15] = [
+        Curve::Triangle,
+        Curve::Sine,
+        Curve::EaseInQuad,
+        Curve::EaseOutQuad,
+        Curve::EaseInOutQuad,
+        Curve::EaseInCubic,
+        Curve::EaseOutCubic,
+        Curve::EaseInOutCubic,
+        Curve::EaseInExpo,
+        Curve::EaseOutExpo,
+        Curve::EaseInOutExpo,
+        Curve::Smoothstep,
+        Curve::Square,
+        Curve::Sawtooth,
+        Curve::Bounce,
+    ];
+
+    pub fn sample(&self, t: f32) -> f32 {
+        let t = t.rem_euclid(1.0);
+        // up and back down again, for the curves that only ease one way
+        let there_and_back = 1.0 - (2.0 * t - 1.0).abs();
+
+        match self {
+            Curve::Triangle => (2.0 * t - 1.0).abs(),
+            Curve::Sine => 0.5 - 0.5 * (2.0 * PI * t).cos(),
+            Curve::EaseInQuad => ease_in_quad(there_and_back),
+            Curve::EaseOutQuad => ease_out_quad(there_and_back),
+            Curve::EaseInOutQuad => ease_in_out(there_and_back, ease_in_quad),
+            Curve::EaseInCubic => ease_in_cubic(there_and_back),
+            Curve::EaseOutCubic => ease_out_cubic(there_and_back),
+            Curve::EaseInOutCubic => ease_in_out(there_and_back, ease_in_cubic),
+            Curve::EaseInExpo => ease_in_expo(there_and_back),
+            Curve::EaseOutExpo => ease_out_expo(there_and_back),
+            Curve::EaseInOutExpo => ease_in_out(there_and_back, ease_in_expo),
+            Curve::Smoothstep => smoothstep(there_and_back),
+            Curve::Square => {
+                if t < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Curve::Sawtooth => t,
+            Curve::Bounce => ease_out_bounce(there_and_back),
+            Curve::Keyframes(keyframes) => sample_keyframes(keyframes, t),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Curve::Triangle => "triangle",
+            Curve::Sine => "sine",
+            Curve::EaseInQuad => "ease in quad",
+            Curve::EaseOutQuad => "ease out quad",
+            Curve::EaseInOutQuad => "ease in-out quad",
+            Curve::EaseInCubic => "ease in cubic",
+            Curve::EaseOutCubic => "ease out cubic",
+            Curve::EaseInOutCubic => "ease in-out cubic",
+            Curve::EaseInExpo => "ease in expo",
+            Curve::EaseOutExpo => "ease out expo",
+            Curve::EaseInOutExpo => "ease in-out expo",
+            Curve::Smoothstep => "smoothstep",
+            Curve::Square => "square",
+            Curve::Sawtooth => "sawtooth",
+            Curve::Bounce => "bounce",
+            Curve::Keyframes(_) => "keyframes",
+        }
+    }
+
+    /// The next of the [`Curve::PRESETS`], keyframes go back to the start.
+    pub fn next_curve(&self) -> Curve {
+        let i = Curve::PRESETS
+            .iter()
+            .position(|c| c == self)
+            .map_or(0, |i| (i + 1) % Curve::PRESETS.len());
+        Curve::PRESETS[i].clone()
+    }
+}
+
+/// `curve` mapped between `min_value` and `max_value`, cycling every `cycle_duration` and shifted
+/// by `phase` (a fraction of a cycle).
+pub fn cycle_curve_over_time(
+    curve: &Curve,
+    current_time: Duration,
+    cycle_duration: Duration,
+    phase: f32,
+    min_value: f32,
+    max_value: f32,
+) -> f32 {
+    let fraction = current_time.as_secs_f32() / cycle_duration.as_secs_f32() + phase;
+    map_range(curve.sample(fraction), 0.0, 1.0, min_value, max_value)
+}
+
+pub fn ease_in_quad(t: f32) -> f32 {
+    t * t
+}
+
+pub fn ease_out_quad(t: f32) -> f32 {
+    1.0 - ease_in_quad(1.0 - t)
+}
+
+pub fn ease_in_cubic(t: f32) -> f32 {
+    t * t * t
+}
+
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - ease_in_cubic(1.0 - t)
+}
+
+pub fn ease_in_expo(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        2.0.powf(10.0 * t - 10.0)
+    }
+}
+
+pub fn ease_out_expo(t: f32) -> f32 {
+    1.0 - ease_in_expo(1.0 - t)
+}
+
+/// Ease in over the first half and out over the second, using `ease_in` for both.
+pub fn ease_in_out(t: f32, ease_in: fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(2.0 * t) / 2.0
+    } else {
+        1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+pub fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+pub fn ease_out_bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+fn sample_keyframes(keyframes: &[(f32, f32)], t: f32) -> f32 {
+    let (first, last) = match (keyframes.first(), keyframes.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return 0.0,
+    };
+
+    let after = keyframes.iter().position(|(k_t, _)| *k_t > t);
+    // wrap around between the last keyframe and the first one of the next cycle
+    let (a, b) = match after {
+        Some(0) => ((last.0 - 1.0, last.1), first),
+        Some(i) => (keyframes[i - 1], keyframes[i]),
+        None => (last, (first.0 + 1.0, first.1)),
+    };
+
+    if b.0 <= a.0 {
+        return a.1;
+    }
+    map_range(t, a.0, b.0, a.1, b.1)
+}
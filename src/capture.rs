@@ -0,0 +1,482 @@
+use std::{
+    fs,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+    str::FromStr,
+    sync::mpsc::{self, SyncSender},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use nannou::{
+    image::{
+        codecs::gif::{GifEncoder, Repeat},
+        Delay, Frame, RgbaImage,
+    },
+    prelude::*,
+};
+
+use crate::metadata::is_latin1;
+use crate::mycelium::growth::LINE_WEIGHT;
+use crate::raster::Raster;
+
+/// Somewhere captured frames go, one after another at a fixed frame rate.
+pub trait FrameSink: Send {
+    fn write_frame(&mut self, frame: &RgbaImage) -> io::Result<()>;
+
+    /// Keyword and text pairs to embed in the output, if the format can carry them. Must be set
+    /// before the first frame.
+    fn set_text_chunks(&mut self, _text_chunks: Vec<(String, String)>) {}
+
+    /// Finish writing, after the last frame.
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// The sink for `path`'s extension: `.gif` for an animated GIF, `.png`/`.apng` for a lossless APNG,
+/// or `.y4m`/`.rgba` for a [`StreamSink`], which also works for named pipes. `-` streams Y4M to
+/// stdout.
+pub fn sink_for_path(path: &Path, fps: u32) -> io::Result<Box<dyn FrameSink>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(StreamSink::stdout(StreamFormat::Y4m, fps)));
+    }
+
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    if let Some(format) = extension.and_then(|ext| ext.parse::<StreamFormat>().ok()) {
+        return Ok(Box::new(StreamSink::create(path, format, fps)?));
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    match extension {
+        Some("gif") => Ok(Box::new(GifSink::create(path, fps)?)),
+        Some("png" | "apng") => Ok(Box::new(ApngSink::create(path, fps)?)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "can't record to {}, use .gif, .png, .y4m, .rgba or -",
+                path.display()
+            ),
+        )),
+    }
+}
+
+/// A [`StreamSink`] of `format` to `path`, whatever its extension, or to stdout for `-`.
+pub fn stream_sink_for_path(
+    path: &Path,
+    format: StreamFormat,
+    fps: u32,
+) -> io::Result<Box<dyn FrameSink>> {
+    if path == Path::new("-") {
+        Ok(Box::new(StreamSink::stdout(format, fps)))
+    } else {
+        Ok(Box::new(StreamSink::create(path, format, fps)?))
+    }
+}
+
+/// Whether [`sink_for_path`] would write a single file or stream for `path`.
+pub fn is_sink_path(path: &Path) -> bool {
+    path == Path::new("-")
+        || path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| matches!(ext, "gif" | "apng" | "y4m" | "rgba"))
+}
+
+/// An endlessly looping animated GIF. Colours are quantised per frame, so gradients band.
+pub struct GifSink {
+    encoder: GifEncoder<BufWriter<fs::File>>,
+    delay: Delay,
+}
+
+impl GifSink {
+    pub fn create(path: &Path, fps: u32) -> io::Result<Self> {
+        // the fastest quantisation that still looks alright, the default is far too slow per frame
+        let mut encoder = GifEncoder::new_with_speed(BufWriter::new(fs::File::create(path)?), 10);
+        encoder.set_repeat(Repeat::Infinite).map_err(to_io_error)?;
+
+        Ok(GifSink {
+            encoder,
+            delay: Delay::from_numer_denom_ms(1000, fps),
+        })
+    }
+}
+
+impl FrameSink for GifSink {
+    fn write_frame(&mut self, frame: &RgbaImage) -> io::Result<()> {
+        self.encoder
+            .encode_frame(Frame::from_parts(frame.clone(), 0, 0, self.delay))
+            .map_err(to_io_error)
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        // the trailer is written when the encoder is dropped
+        drop(self.encoder);
+        Ok(())
+    }
+}
+
+/// An endlessly looping animated PNG, lossless.
+///
+/// APNG needs the number of frames before the first one, so each frame is compressed and written
+/// as it arrives and the count is filled in by [`FrameSink::finish`].
+pub struct ApngSink {
+    file: BufWriter<fs::File>,
+    fps: u32,
+    size: Option<(u32, u32)>,
+    num_frames: u32,
+    /// The next APNG sequence number, shared by frame controls and frame data.
+    sequence: u32,
+    text_chunks: Vec<(String, String)>,
+}
+
+/// Where the acTL chunk starts, straight after the signature and IHDR.
+const ACTL_OFFSET: u64 = 8 + 12 + 13;
+
+impl ApngSink {
+    pub fn create(path: &Path, fps: u32) -> io::Result<Self> {
+        Ok(ApngSink {
+            file: BufWriter::new(fs::File::create(path)?),
+            fps,
+            size: None,
+            num_frames: 0,
+            sequence: 0,
+            text_chunks: vec![],
+        })
+    }
+
+    /// Everything before the first frame, with a frame count to fill in later.
+    fn write_header(&mut self, (width, height): (u32, u32)) -> io::Result<()> {
+        self.file.write_all(&[137, 80, 78, 71, 13, 10, 26, 10])?;
+        let mut ihdr = [width.to_be_bytes(), height.to_be_bytes()].concat();
+        // 8 bit RGBA, no interlacing
+        ihdr.extend([8, 6, 0, 0, 0]);
+        write_chunk(&mut self.file, b"IHDR", &ihdr)?;
+        write_chunk(&mut self.file, b"acTL", &actl(0))?;
+        for (keyword, text) in &self.text_chunks {
+            // tEXt where it fits, like `metadata::save_png`
+            if is_latin1(text) {
+                let latin1: Vec<u8> = text.chars().map(|c| c as u8).collect();
+                write_chunk(
+                    &mut self.file,
+                    b"tEXt",
+                    &[keyword.as_bytes(), &[0], &latin1].concat(),
+                )?;
+            } else {
+                // uncompressed, with no language tag or translated keyword
+                let itxt = [keyword.as_bytes(), &[0, 0, 0, 0, 0], text.as_bytes()].concat();
+                write_chunk(&mut self.file, b"iTXt", &itxt)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FrameSink for ApngSink {
+    fn write_frame(&mut self, frame: &RgbaImage) -> io::Result<()> {
+        let size = match self.size {
+            Some(size) => size,
+            None => {
+                self.write_header(frame.dimensions())?;
+                *self.size.insert(frame.dimensions())
+            }
+        };
+        if frame.dimensions() != size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "frame is {:?} but the animation is {size:?}",
+                    frame.dimensions()
+                ),
+            ));
+        }
+
+        let (width, height) = size;
+        let delay_den = self.fps.min(u16::MAX as u32) as u16;
+        let mut fctl = [self.sequence, width, height, 0, 0]
+            .map(u32::to_be_bytes)
+            .concat();
+        fctl.extend(1u16.to_be_bytes());
+        fctl.extend(delay_den.to_be_bytes());
+        // no disposal, no blending, each frame covers the last completely
+        fctl.extend([0, 0]);
+        write_chunk(&mut self.file, b"fcTL", &fctl)?;
+        self.sequence += 1;
+
+        let data = compress_frame(frame)?;
+        if self.num_frames == 0 {
+            // the first frame doubles as the still image for viewers without APNG support
+            write_chunk(&mut self.file, b"IDAT", &data)?;
+        } else {
+            write_chunk(
+                &mut self.file,
+                b"fdAT",
+                &[&self.sequence.to_be_bytes(), &data[..]].concat(),
+            )?;
+            self.sequence += 1;
+        }
+        self.num_frames += 1;
+        Ok(())
+    }
+
+    fn set_text_chunks(&mut self, text_chunks: Vec<(String, String)>) {
+        self.text_chunks = text_chunks;
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        if self.num_frames == 0 {
+            self.write_frame(&RgbaImage::new(1, 1))?;
+        }
+        write_chunk(&mut self.file, b"IEND", &[])?;
+
+        let mut file = self.file.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(ACTL_OFFSET))?;
+        write_chunk(&mut file, b"acTL", &actl(self.num_frames))?;
+        Ok(())
+    }
+}
+
+/// An acTL chunk's data for `num_frames` looping forever.
+fn actl(num_frames: u32) -> Vec<u8> {
+    [num_frames, 0].map(u32::to_be_bytes).concat()
+}
+
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.finalize().to_be_bytes())
+}
+
+/// `frame` filtered and deflated the way it would be in a PNG of its own.
+fn compress_frame(frame: &RgbaImage) -> io::Result<Vec<u8>> {
+    let mut png = vec![];
+    let mut encoder = png::Encoder::new(&mut png, frame.width(), frame.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(frame.as_raw())?;
+    writer.finish()?;
+
+    // the image data is the IDAT chunks run together, after the 8 byte signature
+    let mut data = vec![];
+    let mut rest = &png[8..];
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let (kind, body) = (&rest[4..8], &rest[8..8 + len]);
+        if kind == b"IDAT" {
+            data.extend_from_slice(body);
+        }
+        rest = &rest[12 + len..];
+    }
+    Ok(data)
+}
+
+/// How a [`StreamSink`] lays out frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// A `RGBA <width> <height> <fps>` line, then every frame as bare 8-bit RGBA rows, top first.
+    Rgba,
+    /// YUV4MPEG2 with full resolution chroma, which most encoders read as is.
+    Y4m,
+}
+
+impl FromStr for StreamFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "rgba" => Ok(StreamFormat::Rgba),
+            "y4m" => Ok(StreamFormat::Y4m),
+            _ => Err(format!("unknown stream format `{name}`, use rgba or y4m")),
+        }
+    }
+}
+
+/// Uncompressed frames written as they come, for piping into an encoder without touching the disk.
+pub struct StreamSink {
+    out: BufWriter<Box<dyn Write + Send>>,
+    format: StreamFormat,
+    fps: u32,
+    size: Option<(u32, u32)>,
+}
+
+impl StreamSink {
+    pub fn new(out: Box<dyn Write + Send>, format: StreamFormat, fps: u32) -> Self {
+        StreamSink {
+            out: BufWriter::new(out),
+            format,
+            fps,
+            size: None,
+        }
+    }
+
+    pub fn stdout(format: StreamFormat, fps: u32) -> Self {
+        StreamSink::new(Box::new(io::stdout()), format, fps)
+    }
+
+    /// Stream to a file or named pipe, which blocks until something opens the other end.
+    pub fn create(path: &Path, format: StreamFormat, fps: u32) -> io::Result<Self> {
+        Ok(StreamSink::new(
+            Box::new(fs::File::create(path)?),
+            format,
+            fps,
+        ))
+    }
+
+    fn write_header(&mut self, (width, height): (u32, u32)) -> io::Result<()> {
+        match self.format {
+            StreamFormat::Rgba => writeln!(self.out, "RGBA {width} {height} {}", self.fps),
+            StreamFormat::Y4m => writeln!(
+                self.out,
+                "YUV4MPEG2 W{width} H{height} F{}:1 Ip A1:1 C444",
+                self.fps
+            ),
+        }
+    }
+}
+
+impl FrameSink for StreamSink {
+    fn write_frame(&mut self, frame: &RgbaImage) -> io::Result<()> {
+        match self.size {
+            None => {
+                self.write_header(frame.dimensions())?;
+                self.size = Some(frame.dimensions());
+            }
+            Some(size) if size != frame.dimensions() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "frame is {:?} but the stream is {size:?}",
+                        frame.dimensions()
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
+
+        match self.format {
+            StreamFormat::Rgba => self.out.write_all(frame.as_raw()),
+            StreamFormat::Y4m => {
+                self.out.write_all(b"FRAME\n")?;
+                for plane in rgba_to_yuv444(frame) {
+                    self.out.write_all(&plane)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Studio range BT.601 Y, Cb and Cr planes, the colour space Y4M readers assume.
+fn rgba_to_yuv444(frame: &RgbaImage) -> [Vec<u8>; 3] {
+    let num_pixels = (frame.width() * frame.height()) as usize;
+    let mut planes = [
+        Vec::with_capacity(num_pixels),
+        Vec::with_capacity(num_pixels),
+        Vec::with_capacity(num_pixels),
+    ];
+
+    for pixel in frame.pixels() {
+        let [r, g, b, _] = pixel.0.map(|c| c as f32 / 255.0);
+        let y = 16.0 + 65.481 * r + 128.553 * g + 24.966 * b;
+        let cb = 128.0 - 37.797 * r - 74.203 * g + 112.0 * b;
+        let cr = 128.0 + 112.0 * r - 93.786 * g - 18.214 * b;
+        for (plane, value) in planes.iter_mut().zip([y, cb, cr]) {
+            plane.push(value.round() as u8);
+        }
+    }
+
+    planes
+}
+
+/// Records frames on a thread of its own, rasterising the polylines of each one and handing them
+/// to a [`FrameSink`], so the sketch isn't held up while it records.
+pub struct Recorder {
+    sender: SyncSender<Vec<(Vec<Point2>, Srgba)>>,
+    thread: JoinHandle<io::Result<()>>,
+    fps: u32,
+    num_frames: u32,
+    world: Rect,
+}
+
+impl Recorder {
+    /// Start recording `world` at `width` by `height` pixels.
+    pub fn start(
+        mut sink: Box<dyn FrameSink>,
+        fps: u32,
+        width: u32,
+        height: u32,
+        world: Rect,
+        background: Srgb<u8>,
+    ) -> Self {
+        // a few frames of slack before the sketch has to wait for the encoder
+        let (sender, receiver) = mpsc::sync_channel::<Vec<(Vec<Point2>, Srgba)>>(4);
+
+        let thread = thread::spawn(move || {
+            for polylines in receiver {
+                let mut raster = Raster::new(width, height, world, background);
+                for (points, colour) in &polylines {
+                    raster.stroke_polyline(points, LINE_WEIGHT, *colour);
+                }
+                sink.write_frame(&raster.to_rgba8())?;
+            }
+            sink.finish()
+        });
+
+        Recorder {
+            sender,
+            thread,
+            fps: fps.max(1),
+            num_frames: 0,
+            world,
+        }
+    }
+
+    /// How much simulation time should pass between frames.
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps as f64)
+    }
+
+    pub fn num_frames(&self) -> u32 {
+        self.num_frames
+    }
+
+    /// The area recorded, frames stay this size however the window changes.
+    pub fn world(&self) -> Rect {
+        self.world
+    }
+
+    /// Queue a frame made of the polylines from [`crate::raster::growth_polylines`].
+    pub fn record(&mut self, polylines: Vec<(Vec<Point2>, Srgba)>) -> io::Result<()> {
+        // the thread only hangs up on an error, which `finish` reports
+        if self.sender.send(polylines).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "recording stopped early",
+            ));
+        }
+        self.num_frames += 1;
+        Ok(())
+    }
+
+    /// Wait for every queued frame to be written and the sink to finish.
+    pub fn finish(self) -> io::Result<()> {
+        drop(self.sender);
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("recorder panicked")))
+    }
+}
+
+fn to_io_error(e: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::other(e)
+}
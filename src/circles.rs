@@ -1,47 +1,76 @@
-use crate::helpers::{cycle_value_over_time, FrameCapture};
+use crate::animation::Curve;
+use crate::helpers::{cycle_value_factory, FrameCapture};
+use crate::metadata::CaptureInfo;
+use crate::mycelium::Config;
+use crate::palette::{Palette, Palettes};
 use nannou::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
 use std::time::Duration;
 
+/// Names the sketch's captures, e.g. the `{sketch}` of the output template.
+pub const SKETCH: &str = "circles";
 pub const WINDOW_SIZE: f32 = 900.0;
 pub const CYCLE_SECONDS: f32 = 10.0;
 pub const CIRCLE_MIN: f32 = WINDOW_SIZE / 10.0;
 pub const CIRCLE_MAX: f32 = WINDOW_SIZE / 3.0;
+/// Frames per second of sketch time. The circles move on by exactly one frame's worth every
+/// update, so captures play back at this rate however fast they were drawn.
+pub const FPS: u32 = 60;
+/// How long to run, and capture, before quitting.
+const DURATION: Duration = Duration::from_secs(10);
 
 pub struct Circle {
     current_radius: f32,
-    min_radius: f32,
-    max_radius: f32,
-    stagger: f32,
+    radius_over_time: Box<dyn Fn(Duration) -> f32>,
+    colour: Srgb<u8>,
 }
 
 impl Circle {
-    pub fn new(min_radius: f32, max_radius: f32, stagger: f32) -> Self {
+    /// A circle breathing between `min_radius` and `max_radius` along `curve`, `stagger` of a cycle
+    /// behind the others.
+    pub fn new(
+        min_radius: f32,
+        max_radius: f32,
+        stagger: f32,
+        curve: Curve,
+        colour: Srgb<u8>,
+    ) -> Self {
         Circle {
             current_radius: min_radius,
-            min_radius,
-            max_radius,
-            stagger,
+            radius_over_time: Box::new(cycle_value_factory(
+                curve,
+                Duration::from_secs_f32(CYCLE_SECONDS),
+                stagger,
+                min_radius,
+                max_radius,
+            )),
+            colour,
         }
     }
 
     pub fn calculate_circle_radius(&mut self, since_start: Duration) {
-        self.current_radius = cycle_value_over_time(
-            since_start + (Duration::from_millis((CYCLE_SECONDS * 1000.0 * self.stagger) as u64)),
-            Duration::from_secs_f32(CYCLE_SECONDS),
-            // + (CYCLE_SECONDS * self.stagger)),
-            self.min_radius,
-            self.max_radius,
-        )
+        self.current_radius = (self.radius_over_time)(since_start)
     }
 
     pub fn radius(&self) -> f32 {
         self.current_radius
     }
+
+    pub fn colour(&self) -> Srgb<u8> {
+        self.colour
+    }
+
+    pub fn set_colour(&mut self, colour: Srgb<u8>) {
+        self.colour = colour;
+    }
 }
 
 struct Model {
     circles: Vec<Circle>,
     frame_capture: FrameCapture,
+    palettes: Palettes,
 }
 
 impl Model {
@@ -50,23 +79,61 @@ impl Model {
             c.calculate_circle_radius(since_start);
         }
     }
+
+    fn colour_circles(&mut self) {
+        let palette: &Palette = self.palettes.current();
+        let mut rng = thread_rng();
+        for c in &mut self.circles {
+            c.set_colour(palette.choose(&mut rng));
+        }
+    }
 }
 
 pub fn main() {
     nannou::app(model)
         .loop_mode(LoopMode::RefreshSync)
         .update(update)
-        .simple_window(view)
-        .size(WINDOW_SIZE as u32, WINDOW_SIZE as u32)
         .run();
 }
 
 fn model(app: &App) -> Model {
+    app.new_window()
+        .size(WINDOW_SIZE as u32, WINDOW_SIZE as u32)
+        .view(view)
+        .key_pressed(key_pressed)
+        .build()
+        .unwrap();
+
+    let palettes = Palettes::load_from_app(app);
+    let seed: u64 = random();
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let frame_capture = FrameCapture::new_from_app_with_seed(app, SKETCH, &seed.to_string());
+    let mut info = CaptureInfo::new(
+        SKETCH,
+        seed,
+        &settings(),
+        (WINDOW_SIZE as u32, WINDOW_SIZE as u32),
+    );
+    info.palette = palettes.current().name.clone();
+    info.fps = Some(FPS);
+    if let Err(e) = frame_capture.write_manifest(&info) {
+        eprintln!("failed to write the capture manifest: {e}");
+    }
+
     let mut model = Model {
         circles: (0..3)
-            .map(|i| Circle::new(CIRCLE_MIN, CIRCLE_MAX, 0.05 * i as f32))
+            .map(|i| {
+                Circle::new(
+                    CIRCLE_MIN,
+                    CIRCLE_MAX,
+                    0.05 * i as f32,
+                    Curve::Triangle,
+                    palettes.current().choose(&mut rng),
+                )
+            })
             .collect(),
-        frame_capture: FrameCapture::new_from_app(app),
+        frame_capture,
+        palettes,
     };
 
     model.step_circles(Duration::from_micros(0));
@@ -74,19 +141,40 @@ fn model(app: &App) -> Model {
     model
 }
 
+/// The constants the circles are drawn with, for the capture manifest.
+fn settings() -> Config {
+    Config::new(HashMap::from([
+        ("cycle_seconds".to_string(), CYCLE_SECONDS),
+        ("circle_min".to_string(), CIRCLE_MIN),
+        ("circle_max".to_string(), CIRCLE_MAX),
+    ]))
+}
+
 fn update(app: &App, model: &mut Model, _update: Update) {
-    model.step_circles(app.duration.since_start);
+    model.step_circles(sketch_time(app));
+}
+
+/// How far into the sketch the current frame is, at [`FPS`].
+fn sketch_time(app: &App) -> Duration {
+    Duration::from_secs(1) * app.elapsed_frames() as u32 / FPS
+}
+
+fn key_pressed(_app: &App, model: &mut Model, key: Key) {
+    if key == Key::P {
+        model.palettes.next_palette();
+        model.colour_circles();
+    }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
     let window_rect = app.window_rect();
-    draw.background().color(BLACK);
+    draw.background().color(model.palettes.current().background);
     for c in &model.circles {
         draw.translate(window_rect.xy().extend(0.0));
         draw.ellipse()
             .stroke_weight(3.0)
-            .stroke(BLANCHEDALMOND)
+            .stroke(c.colour())
             .no_fill()
             .radius(c.radius())
             .x_y(0.0, 0.0);
@@ -96,7 +184,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
     // Capture the frame!
     model.frame_capture.capture_main_window_frame(app);
 
-    if app.duration.since_start >= Duration::from_secs(10) {
+    if sketch_time(app) >= DURATION {
         app.quit()
     }
 }
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, error::Error, str::FromStr};
+
+/// Command line arguments as positional values and `--name value` or `--flag` options.
+#[derive(Clone, Debug, Default)]
+pub struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    pub fn parse(args: &[String]) -> Self {
+        let mut parsed = Args::default();
+        let mut args = args.iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args.next_if(|next| !next.starts_with("--")).cloned();
+                    parsed.options.insert(name.to_string(), value);
+                }
+                None => parsed.positional.push(arg.clone()),
+            }
+        }
+
+        parsed
+    }
+
+    /// The arguments the program was started with, after its name.
+    pub fn from_env() -> Self {
+        Args::parse(&std::env::args().skip(1).collect::<Vec<_>>())
+    }
+
+    pub fn positional(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(String::as_str)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    /// The value of `--name`, parsed, or `None` if it wasn't given.
+    pub fn get<T>(&self, name: &str) -> Result<Option<T>, Box<dyn Error>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        match self.options.get(name) {
+            None => Ok(None),
+            Some(None) => Err(format!("`--{name}` needs a value").into()),
+            Some(Some(value)) => value
+                .parse()
+                .map(Some)
+                .map_err(|e| format!("`--{name} {value}`: {e}").into()),
+        }
+    }
+
+    /// The comma separated values of `--name`, parsed, or `None` if it wasn't given.
+    pub fn get_list<T>(&self, name: &str) -> Result<Option<Vec<T>>, Box<dyn Error>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let Some(list) = self.get::<String>(name)? else {
+            return Ok(None);
+        };
+        list.split(',')
+            .map(|value| {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("`--{name} {list}`: {e}").into())
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    pub fn get_or<T>(&self, name: &str, default: T) -> Result<T, Box<dyn Error>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        Ok(self.get(name)?.unwrap_or(default))
+    }
+}
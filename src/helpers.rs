@@ -1,79 +1,135 @@
-use std::{path::PathBuf, time::Duration};
+use std::{
+    cell::{Cell, RefCell},
+    fs, io,
+    path::PathBuf,
+    time::Duration,
+};
 
 use nannou::prelude::*;
 use rand::prelude::*;
 
+use crate::animation::{cycle_curve_over_time, Curve};
+use crate::cli::Args;
+use crate::metadata::CaptureInfo;
+use crate::output::{project_dir, FrameNames, DEFAULT_TEMPLATE};
+
+/// A linear triangle wave, starting at `max_value`. See [`cycle_curve_over_time`] for other
+/// shapes.
 pub fn cycle_value_over_time(
     current_time: Duration,
     cycle_duration: Duration,
     min_value: f32,
     max_value: f32,
 ) -> f32 {
-    let fraction = (current_time.div_f32(cycle_duration.as_secs_f32()))
-        .as_secs_f32()
-        .fract();
-    let cycled_fraction = (fraction - 0.5).abs();
-    return map_range(cycled_fraction, 0.0, 0.5, min_value, max_value);
+    cycle_curve_over_time(
+        &Curve::Triangle,
+        current_time,
+        cycle_duration,
+        0.0,
+        min_value,
+        max_value,
+    )
 }
 
 pub fn cycle_value_factory(
+    curve: Curve,
     cycle_duration: Duration,
+    phase: f32,
     min_value: f32,
     max_value: f32,
 ) -> impl Fn(Duration) -> f32 {
     move |current_time: Duration| {
-        cycle_value_over_time(current_time, cycle_duration, min_value, max_value)
+        cycle_curve_over_time(
+            &curve,
+            current_time,
+            cycle_duration,
+            phase,
+            min_value,
+            max_value,
+        )
     }
 }
 
+/// Saves frames of the main window under the output root, named by the output template, see
+/// [`FrameNames`].
 pub struct FrameCapture {
-    /// Create a folder path that we want to save the frames to
-    dir_path: PathBuf,
+    names: FrameNames,
+    /// The folder the last frame went in, so it's only created when it changes.
+    dir: RefCell<Option<PathBuf>>,
+    /// Set once frames can't be saved, so it's reported once rather than every frame.
+    failed: Cell<bool>,
 }
 
 impl FrameCapture {
-    /// Standard directory called `/<path_to_project>/output/<start_time>`.
-    pub fn new_from_app(app: &App) -> Self {
-        let start_time = chrono::Local::now().format("%Y-%m-%d:%H:%M:%S");
-
-        return FrameCapture {
-            dir_path: app
-                .project_path()
-                .expect("failed to locate `project_path`")
-                .join("output")
-                .join(start_time.to_string()),
-        };
+    /// Frames of `sketch` go in `<output root>/<output template>`, by default
+    /// `/<path_to_project>/output/<sketch>/<start_time>/<frame>.png`. Bad `--output-root` or
+    /// `--output-template` arguments are reported and the defaults used instead.
+    pub fn new_from_app(app: &App, sketch: &str) -> Self {
+        let default_root = project_dir(app).join("output");
+        let names =
+            FrameNames::from_args(&Args::from_env(), &default_root, sketch).unwrap_or_else(|e| {
+                eprintln!("{e}, saving frames to the default place instead");
+                let mut names = FrameNames::new(&default_root, DEFAULT_TEMPLATE).unwrap();
+                names.set("sketch", sketch);
+                names
+            });
+
+        FrameCapture {
+            names,
+            dir: RefCell::new(None),
+            failed: Cell::new(false),
+        }
     }
 
     pub fn capture_main_window_frame(&self, app: &App) {
-        let file_path = self
-            .dir_path
-            .join(format!("{:05}", app.elapsed_frames() + 1))
-            .with_extension("png");
+        if self.failed.get() {
+            return;
+        }
+
+        let file_path = self.names.frame_path(app.elapsed_frames() + 1);
+        if let Some(dir) = file_path.parent() {
+            let mut created = self.dir.borrow_mut();
+            if created.as_deref() != Some(dir) {
+                if let Err(e) = fs::create_dir_all(dir) {
+                    eprintln!("can't save frames to {}: {e}", dir.display());
+                    self.failed.set(true);
+                    return;
+                }
+                *created = Some(dir.to_path_buf());
+            }
+        }
 
         app.main_window().capture_frame(file_path);
     }
 
-    pub fn new_from_app_with_seed(app: &App, seed: &str) -> Self {
-        let start_time = chrono::Local::now().format("%Y-%m-%d:%H:%M:%S");
+    /// Write how the frames were made to `manifest.json` in the capture folder. Frames come
+    /// straight from the GPU so, unlike the CPU captures, they can't carry it themselves.
+    pub fn write_manifest(&self, info: &CaptureInfo) -> io::Result<()> {
+        info.write_manifest(&self.names.capture_dir().join("manifest.json"))
+    }
 
-        return FrameCapture {
-            dir_path: app
-                .project_path()
-                .expect("failed to locate `project_path`")
-                .join("output")
-                .join(format!("{}-{}", start_time.to_string(), seed)),
-        };
+    pub fn new_from_app_with_seed(app: &App, sketch: &str, seed: &str) -> Self {
+        let mut frame_capture = FrameCapture::new_from_app(app, sketch);
+        frame_capture.names.set("seed", seed);
+        frame_capture
+    }
+
+    /// Fill in a token of the output template, e.g. `preset`.
+    pub fn set_token(&mut self, token: &'static str, value: &str) {
+        self.names.set(token, value);
     }
 }
 
 pub fn rand_normalised_vec() -> Vec2 {
+    rand_normalised_vec_with_rng(&mut thread_rng())
+}
+
+pub fn rand_normalised_vec_with_rng<R: Rng>(rng: &mut R) -> Vec2 {
     let rand_amount = 100;
-    let mut rng = thread_rng();
     let x = rng.gen_range(-rand_amount..=rand_amount) as f32;
     let y = rng.gen_range(-rand_amount..=rand_amount) as f32;
 
-    vec2(x, y).normalize()
+    vec2(x, y).normalize_or_zero()
 }
 
 pub trait RandFromSlice<T> {
@@ -1,3 +1,13 @@
+pub mod animation;
+pub mod capture;
 pub mod circles;
+pub mod cli;
 pub mod helpers;
-pub mod mycelium;
\ No newline at end of file
+pub mod metadata;
+pub mod mycelium;
+pub mod output;
+pub mod palette;
+pub mod raster;
+pub mod sheet;
+pub mod simplify;
+pub mod smoothing;
@@ -1,8 +1,23 @@
 #[allow(unused_imports)]
-
-use liturgy_for_the_mushroom_at_the_end_of_the_world::{circles, mycelium};
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{circles, cli::Args, mycelium};
 
 fn main() {
-    // circles::main();
-    mycelium::main();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("render") => mycelium::render::run(&Args::parse(&args[1..])),
+        Some("animate") => mycelium::render::run_animation(&Args::parse(&args[1..])),
+        Some("sweep") => mycelium::sweep::run_seed_sweep(&Args::parse(&args[1..])),
+        Some("grid") => mycelium::sweep::run_grid_sweep(&Args::parse(&args[1..])),
+        _ => {
+            // circles::main();
+            mycelium::main();
+            Ok(())
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,160 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use nannou::image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::mycelium::Config;
+
+/// How a capture was made, enough to make it again: written next to captures as a JSON manifest
+/// and into PNGs as text chunks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaptureInfo {
+    pub sketch: String,
+    /// Version of this crate that made the capture.
+    pub version: String,
+    pub seed: u64,
+    pub config: BTreeMap<String, f32>,
+    pub palette: String,
+    pub smoothing: String,
+    /// Which growths are linked to which, see [`crate::mycelium::topology::TopologyMode`].
+    #[serde(default)]
+    pub topology: String,
+    /// Where growth centres were placed, see [`crate::mycelium::placement::PlacementMode`].
+    #[serde(default)]
+    pub placement: String,
+    /// Timeline file changing the config over time, if there was one.
+    pub timeline: Option<String>,
+    /// Species file the growths were made from, if there was one.
+    #[serde(default)]
+    pub species: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    /// Frames per second of simulation time, for animations.
+    pub fps: Option<u32>,
+    /// When the capture started, RFC 3339.
+    pub started: String,
+}
+
+impl CaptureInfo {
+    /// Info for a capture of `sketch` starting now, fill in `palette`, `smoothing`, `timeline` and
+    /// `fps` where they apply.
+    pub fn new(sketch: &str, seed: u64, config: &Config, (width, height): (u32, u32)) -> Self {
+        CaptureInfo {
+            sketch: sketch.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            seed,
+            config: config
+                .values()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+            palette: String::new(),
+            smoothing: String::new(),
+            topology: String::new(),
+            placement: String::new(),
+            timeline: None,
+            species: None,
+            width,
+            height,
+            fps: None,
+            started: chrono::Local::now().to_rfc3339(),
+        }
+    }
+
+    pub fn write_manifest(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer_pretty(file, self).map_err(io::Error::from)
+    }
+
+    pub fn load_manifest(path: &Path) -> io::Result<Self> {
+        let file = io::BufReader::new(fs::File::open(path)?);
+        serde_json::from_reader(file).map_err(io::Error::from)
+    }
+
+    /// Keyword and text pairs for PNG text chunks, the whole info is under `Comment` as JSON. The
+    /// JSON escapes anything outside ASCII, so it always fits the `tEXt` chunk viewers look in.
+    pub fn text_chunks(&self) -> Vec<(String, String)> {
+        vec![
+            (
+                "Software".to_string(),
+                format!("{} {}", self.sketch, self.version),
+            ),
+            ("Seed".to_string(), self.seed.to_string()),
+            (
+                "Comment".to_string(),
+                ascii_json(&serde_json::to_string(self).unwrap_or_default()),
+            ),
+        ]
+    }
+}
+
+/// `json` with every character outside ASCII written as `\u` escapes, which can only be inside
+/// strings, so it still parses the same.
+fn ascii_json(json: &str) -> String {
+    let mut ascii = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            ascii.push(c);
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                ascii.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+    }
+    ascii
+}
+
+/// Whether `text` fits a `tEXt` chunk, which only holds Latin-1. Anything else needs an `iTXt`.
+pub fn is_latin1(text: &str) -> bool {
+    text.chars().all(|c| (c as u32) < 0x100)
+}
+
+/// Save `image` as a PNG carrying `text_chunks` as `tEXt` chunks, or as `iTXt` chunks for text
+/// that isn't Latin-1, e.g. a frame named after a palette from a `.gpl` file.
+pub fn save_png(
+    image: &RgbaImage,
+    path: &Path,
+    text_chunks: &[(String, String)],
+) -> io::Result<()> {
+    let file = BufWriter::new(fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (keyword, text) in text_chunks {
+        if is_latin1(text) {
+            encoder.add_text_chunk(keyword.clone(), text.clone())?;
+        } else {
+            encoder.add_itxt_chunk(keyword.clone(), text.clone())?;
+        }
+    }
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(image.as_raw())?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// The uncompressed `tEXt` and `iTXt` chunks of the PNG at `path`.
+pub fn read_png_text(path: &Path) -> io::Result<Vec<(String, String)>> {
+    let reader = png::Decoder::new(fs::File::open(path)?).read_info()?;
+    let info = reader.info();
+    let latin1 = info
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| Ok((chunk.keyword.clone(), chunk.text.clone())));
+    let utf8 = info
+        .utf8_text
+        .iter()
+        .map(|chunk| Ok((chunk.keyword.clone(), chunk.get_text()?)));
+    latin1
+        .chain(utf8)
+        .collect::<Result<_, png::DecodingError>>()
+        .map_err(io::Error::from)
+}
@@ -0,0 +1,198 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use nannou::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::palette::Palette;
+
+use super::simulation::Simulation;
+use super::timeline::Timeline;
+use super::Config;
+
+/// Candidates shown a generation, as a square grid.
+pub const GRID_SIZE: usize = 3;
+/// Config values that mutate between generations, the rest are passed down as they are.
+pub const MUTABLE_KEYS: [&str; 4] = [
+    "max_vary_amount",
+    "step_amount",
+    "rand_factor",
+    "simplify_tolerance",
+];
+/// Where the parent is shown, so it's easy to tell apart from its children.
+const PARENT_CELL: usize = GRID_SIZE * GRID_SIZE / 2;
+
+/// Everything that makes a simulation turn out the way it does.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Genome {
+    pub seed: u64,
+    pub config: Config,
+}
+
+impl Genome {
+    /// A child with a new seed and each of the [`MUTABLE_KEYS`] scaled by up to `rate` either way.
+    pub fn mutate<R: Rng>(&self, rate: f32, rng: &mut R) -> Genome {
+        let mut config = self.config.clone();
+        let rate = rate.clamp(0.0, 1.0);
+
+        for key in MUTABLE_KEYS {
+            let value = config.get_with_default(key, 0.0);
+            let factor = 1.0 + rng.gen_range(-rate..=rate);
+            config.set(key, (value * factor).max(0.0));
+        }
+
+        Genome {
+            seed: rng.gen(),
+            config,
+        }
+    }
+}
+
+/// A generation of candidates bred from a parent, each running in its own cell of a grid.
+pub struct Breeding {
+    pub generation: u32,
+    /// The genome of each cell, the parent in the middle.
+    pub genomes: Vec<Genome>,
+    /// Every chosen parent, oldest first.
+    pub lineage: Vec<Genome>,
+    /// Where the lineage is saved each time a parent is chosen, including the first.
+    lineage_path: Option<PathBuf>,
+    simulations: Vec<Simulation>,
+    world: Rect,
+    palette: Palette,
+    rng: ChaCha8Rng,
+}
+
+impl Breeding {
+    /// Start breeding from `parent`, with simulations the size of `world`, saving the lineage to
+    /// `lineage_path` as it grows if there is one.
+    pub fn new(
+        parent: Genome,
+        rate: f32,
+        world: Rect,
+        palette: Palette,
+        seed: u64,
+        lineage_path: Option<PathBuf>,
+    ) -> Self {
+        let mut breeding = Breeding {
+            generation: 0,
+            genomes: vec![],
+            lineage: vec![],
+            lineage_path,
+            simulations: vec![],
+            world,
+            palette,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        };
+        breeding.breed(parent, rate);
+        breeding
+    }
+
+    fn breed(&mut self, parent: Genome, rate: f32) {
+        self.genomes = (0..GRID_SIZE * GRID_SIZE)
+            .map(|i| {
+                if i == PARENT_CELL {
+                    parent.clone()
+                } else {
+                    parent.mutate(rate, &mut self.rng)
+                }
+            })
+            .collect();
+        self.simulations = self
+            .genomes
+            .iter()
+            .map(|genome| {
+                Simulation::new(
+                    genome.seed,
+                    self.world,
+                    genome.config.clone(),
+                    self.palette.clone(),
+                    Timeline::default(),
+                )
+                .start()
+            })
+            .collect();
+        self.lineage.push(parent);
+
+        if let Some(path) = &self.lineage_path {
+            if let Err(e) = self.save_lineage(path) {
+                eprintln!("failed to save lineage to {}: {e}", path.display());
+            }
+        }
+    }
+
+    /// Make the candidate in `cell` the parent of the next generation.
+    pub fn choose(&mut self, cell: usize, rate: f32) {
+        if let Some(parent) = self.genomes.get(cell).cloned() {
+            self.generation += 1;
+            self.breed(parent, rate);
+        }
+    }
+
+    pub fn lineage_path(&self) -> Option<&Path> {
+        self.lineage_path.as_deref()
+    }
+
+    /// The most recently chosen parent.
+    pub fn parent(&self) -> &Genome {
+        self.lineage.last().unwrap()
+    }
+
+    pub fn step(&mut self, dt: Duration) {
+        for simulation in &mut self.simulations {
+            simulation.step(dt);
+        }
+    }
+
+    /// Each simulation shrunk into its cell of `window_rect`.
+    pub fn draw(&self, draw: &Draw, window_rect: Rect) {
+        let scale = cell_rect(window_rect, 0).w() / self.world.w();
+
+        for (i, simulation) in self.simulations.iter().enumerate() {
+            let cell = cell_rect(window_rect, i);
+            let cell_draw = draw.scissor(cell).xy(cell.xy()).scale(scale);
+            simulation.draw(&cell_draw);
+
+            if i == PARENT_CELL {
+                draw.rect()
+                    .xy(cell.xy())
+                    .wh(cell.wh())
+                    .no_fill()
+                    .stroke_weight(2.0)
+                    .stroke(WHEAT);
+            }
+        }
+    }
+
+    pub fn save_lineage(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = io::BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer_pretty(file, &self.lineage).map_err(io::Error::from)
+    }
+}
+
+/// Cell `i` of the grid, counting across from the top left.
+pub fn cell_rect(window_rect: Rect, i: usize) -> Rect {
+    let size = window_rect.w().min(window_rect.h()) / GRID_SIZE as f32;
+    let (column, row) = (i % GRID_SIZE, i / GRID_SIZE);
+    let grid_width = size * GRID_SIZE as f32;
+    Rect::from_x_y_w_h(
+        window_rect.x() - grid_width / 2.0 + size * (column as f32 + 0.5),
+        window_rect.y() + grid_width / 2.0 - size * (row as f32 + 0.5),
+        size,
+        size,
+    )
+    .pad(4.0)
+}
+
+/// The cell under `point`, if there is one.
+pub fn cell_at(window_rect: Rect, point: Point2) -> Option<usize> {
+    (0..GRID_SIZE * GRID_SIZE).find(|i| cell_rect(window_rect, *i).contains(point))
+}
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+/// Speeds the simulation can be scaled to, slowest first.
+pub const TIME_SCALES: [f32; 9] = [0.1, 0.25, 0.5, 1.0, 1.5, 2.0, 3.0, 5.0, 10.0];
+
+/// How much simulation time a tick is. Lines wander by the same amount every tick however long it
+/// is, so the simulation always steps by whole ticks of this and only how many of them run per
+/// update changes with the time scale.
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+/// Most ticks run in one real-time update, so a slow frame at a high time scale can't snowball.
+const MAX_TICKS: u32 = 30;
+
+/// Simulation time, which can be paused, stepped a tick at a time and sped up or slowed down
+/// independently of real time.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    paused: bool,
+    step_requested: bool,
+    time_scale: usize,
+    elapsed: Duration,
+    /// Scaled time not yet run as a whole tick.
+    pending: Duration,
+    /// Used instead of the real update's length, e.g. to record at a steady frame rate.
+    fixed_dt: Option<Duration>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock {
+            paused: false,
+            step_requested: false,
+            time_scale: TIME_SCALES.iter().position(|s| *s == 1.0).unwrap(),
+            elapsed: Duration::ZERO,
+            pending: Duration::ZERO,
+            fixed_dt: None,
+        }
+    }
+}
+
+impl Clock {
+    /// A clock stepping exactly one frame's worth of time every update, for frames made offline.
+    pub fn at_fps(fps: u32) -> Self {
+        let mut clock = Clock::default();
+        clock.set_fixed_dt(Some(Duration::from_secs_f64(1.0 / fps as f64)));
+        clock
+    }
+
+    /// Advance by a real update of `real_dt`, returning how many [`TICK`]s the simulation should
+    /// step, which can be none at slow time scales, or `None` if it shouldn't step at all.
+    pub fn tick(&mut self, real_dt: Duration) -> Option<u32> {
+        if self.paused {
+            if !self.step_requested {
+                return None;
+            }
+            self.step_requested = false;
+            self.elapsed += TICK;
+            return Some(1);
+        }
+
+        // f32 would round frame lengths off by whole nanoseconds, which add up to lost ticks
+        self.pending += self
+            .fixed_dt
+            .unwrap_or(real_dt)
+            .mul_f64(self.time_scale() as f64);
+        // fixed updates are all meant to be run, however slow they are to draw
+        if self.fixed_dt.is_none() {
+            self.pending = self.pending.min(TICK * MAX_TICKS);
+        }
+        let ticks = (self.pending.as_nanos() / TICK.as_nanos()) as u32;
+        self.pending -= TICK * ticks;
+        self.elapsed += TICK * ticks;
+        Some(ticks)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    /// Step by `fixed_dt` every update however long it really took, or by real time for `None`.
+    pub fn set_fixed_dt(&mut self, fixed_dt: Option<Duration>) {
+        self.fixed_dt = fixed_dt;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_paused(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Step exactly one tick on the next update, only while paused.
+    pub fn request_step(&mut self) {
+        if self.paused {
+            self.step_requested = true;
+        }
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        TIME_SCALES[self.time_scale]
+    }
+
+    pub fn faster(&mut self) {
+        self.time_scale = (self.time_scale + 1).min(TIME_SCALES.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.time_scale = self.time_scale.saturating_sub(1);
+    }
+}
@@ -1,52 +1,95 @@
 use nannou::prelude::*;
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Mul, Sub};
+use std::time::Duration;
 
-use crate::helpers::rand_normalised_vec;
+use crate::helpers::{rand_normalised_vec, rand_normalised_vec_with_rng};
+use crate::simplify::douglas_peucker;
+use crate::smoothing::Smoothing;
 
+use super::species::Genes;
 use super::Config;
 
-#[derive(Clone, Debug)]
+/// Stroke weight of every line, in the sketch's coordinates.
+pub const LINE_WEIGHT: f32 = 3.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Growth {
     pub centre: Point2,
     pub lines: Vec<Line>,
     pub colour: Srgba,
+    /// Index of the growth's species in the species it was made from.
+    #[serde(default)]
+    pub species: usize,
+    #[serde(default)]
+    pub genes: Genes,
 }
 
 impl Growth {
-    pub fn new(centre: Point2, other_growths: &[Point2], colour: Srgb<u8>) -> Self {
+    /// A line to each of `other_growths` other than `centre` itself. Every line gets its own rng
+    /// seeded from `rng`, so lines can be stepped in any order (or in parallel) and still grow the
+    /// same way.
+    pub fn new<R: Rng>(
+        centre: Point2,
+        other_growths: &[Point2],
+        colour: Srgb<u8>,
+        rng: &mut R,
+    ) -> Self {
         let colour: Srgb<f32> = Srgb::from_format(colour);
         let (r, g, b) = colour.into_components();
         Growth {
             centre,
             lines: other_growths
                 .iter()
-                .map(|p_c| Line::new(centre, *p_c))
+                .filter(|p_c| **p_c != centre)
+                .map(|p_c| Line::new(centre, *p_c, ChaCha8Rng::seed_from_u64(rng.gen())))
                 .collect(),
             colour: Srgba::new(r, g, b, 0.1),
+            species: 0,
+            genes: Genes::default(),
         }
     }
 
+    /// The growth as one of species `species`, growing by its `genes`.
+    pub fn with_species(mut self, species: usize, genes: Genes) -> Self {
+        self.species = species;
+        self.genes = genes;
+        self
+    }
+
     pub fn is_finished(&self) -> bool {
-        self.lines
-            .iter()
-            .fold(true, |acc, line| acc && line.finished)
+        self.lines.iter().all(|line| line.is_finished())
+    }
+
+    pub fn set_colour(&mut self, colour: Srgb<u8>) {
+        let colour: Srgb<f32> = Srgb::from_format(colour);
+        let (r, g, b) = colour.into_components();
+        self.colour = Srgba::new(r, g, b, self.colour.alpha);
     }
 
-    pub fn step_growth(&mut self, app: &App, config: &Config) {
+    pub fn step_growth(&mut self, dt: Duration, config: &Config) {
         for l in &mut self.lines {
-            l.step_line(app, config)
+            l.step_line_with_genes(dt, config, &self.genes)
         }
     }
 
-    pub fn draw(&self, draw: &Draw, amount: f32) {
+    pub fn par_step_growth(&mut self, dt: Duration, config: &Config) {
+        self.lines
+            .par_iter_mut()
+            .for_each(|l| l.step_line_with_genes(dt, config, &self.genes));
+    }
+
+    pub fn draw(&self, draw: &Draw, amount: f32, smoothing: &Smoothing) {
         for line in &self.lines {
-            line.draw(draw, &self.colour, amount)
+            line.draw(draw, &self.colour, amount, smoothing)
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Copy, Serialize, Deserialize)]
 pub struct Point(Point2, Vec2);
 
 impl Point {
@@ -58,8 +101,16 @@ impl Point {
         Self(point2, rand_normalised_vec())
     }
 
+    pub fn new_with_rng<R: Rng>(point2: Point2, rng: &mut R) -> Self {
+        Self(point2, rand_normalised_vec_with_rng(rng))
+    }
+
+    pub fn with_variation(point2: Point2, variation: Vec2) -> Self {
+        Self(point2, variation)
+    }
+
     pub fn normalize(&self) -> Self {
-        Self(self.0.normalize(), self.1)
+        Self(self.0.normalize_or_zero(), self.1)
     }
 
     pub fn vary_by_amount(&self, amount: f32) -> Point2 {
@@ -83,7 +134,7 @@ impl Add for Point {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, (self.1 + rhs.1).normalize())
+        Self(self.0 + rhs.0, (self.1 + rhs.1).normalize_or_zero())
     }
 }
 
@@ -99,7 +150,7 @@ impl Sub for Point {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, (self.1 - rhs.1).normalize())
+        Self(self.0 - rhs.0, (self.1 - rhs.1).normalize_or_zero())
     }
 }
 
@@ -115,7 +166,7 @@ impl Mul for Point {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        Self(self.0 * rhs.0, (self.1 * rhs.1).normalize())
+        Self(self.0 * rhs.0, (self.1 * rhs.1).normalize_or_zero())
     }
 }
 
@@ -135,66 +186,138 @@ impl Mul<f32> for Point {
     }
 }
 
-#[derive(Clone, Debug)]
+/// Where a line is in its life.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineState {
+    Growing,
+    /// Reached its end point.
+    Arrived,
+    /// Stopped getting any closer to its end point for `stuck_steps` steps.
+    Stuck,
+    /// Gave up after `max_steps` without arriving.
+    Abandoned,
+    /// Reached ground another species claimed first, only when species compete, see
+    /// [`super::species::Territory::claim`].
+    Dormant,
+}
+
+impl LineState {
+    pub fn is_growing(&self) -> bool {
+        *self == LineState::Growing
+    }
+
+    /// Lines that gave up fade into the background.
+    pub fn alpha_factor(&self) -> f32 {
+        match self {
+            LineState::Stuck | LineState::Abandoned => 0.5,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Line {
     pub start: Point2,
     pub end: Point2,
     pub points: Vec<Point>,
-    pub finished: bool,
+    pub state: LineState,
+    pub steps: u32,
+    /// Closest the line has been to `end`, and how many steps ago it last got closer.
+    pub closest: f32,
+    pub steps_since_progress: u32,
+    pub rng: ChaCha8Rng,
 }
 
 impl Line {
-    pub fn new(start: Point2, end: Point2) -> Self {
+    pub fn new(start: Point2, end: Point2, rng: ChaCha8Rng) -> Self {
         Line {
             start,
             end,
             points: vec![Point(start, vec2(0.0, 0.0))],
-            finished: false,
+            state: LineState::Growing,
+            steps: 0,
+            closest: start.distance(end),
+            steps_since_progress: 0,
+            rng,
         }
     }
 
-    pub fn step_line(&mut self, app: &App, config: &Config) {
-        if !self.finished {
+    /// Anything other than growing, a finished line won't be stepped again.
+    pub fn is_finished(&self) -> bool {
+        !self.state.is_growing()
+    }
+
+    pub fn step_line(&mut self, dt: Duration, config: &Config) {
+        self.step_line_with_genes(dt, config, &Genes::default())
+    }
+
+    pub fn step_line_with_genes(&mut self, dt: Duration, config: &Config, genes: &Genes) {
+        if self.state.is_growing() {
+            if self.steps as f32 >= config.get_with_default("max_steps", 10000.0) {
+                self.finish(LineState::Abandoned, config);
+            } else if self.steps_since_progress as f32
+                >= config.get_with_default("stuck_steps", 600.0)
+            {
+                self.finish(LineState::Stuck, config);
+            }
+        }
+
+        if self.state.is_growing() {
+            self.steps += 1;
             if let Some(p_last) = self.points.last() {
                 // check if we're within x pixels of the "end point" and return that
                 let length = 2.0;
                 let d_left = p_last.0.distance(self.end);
-                let p_next = if d_left <= length * 2.0 {
-                    self.finished = true;
+                let arrived = d_left <= length * 2.0;
+                let p_next = if arrived {
                     Point(self.end, vec2(0.0, 0.0))
                 } else {
                     // randomise where the end point is for fun, curly lines
-                    let rand_amount = 100;
-                    let mut rng = thread_rng();
-
-                    let p_random = vec2(
-                        rng.gen_range(-rand_amount..=rand_amount) as f32,
-                        rng.gen_range(-rand_amount..=rand_amount) as f32,
-                    )
-                    .normalize()
-                        * config.get_with_default("rand_factor", 1.5);
+                    let rng = &mut self.rng;
+                    let p_random = rand_normalised_vec_with_rng(rng) * genes.rand_factor(config);
 
                     // get the vector towards the "end point"
                     let v_to_end = (*p_last - self.end).normalize();
 
                     // move towards the end point and add random for fun
-                    Point::new(
+                    Point::new_with_rng(
                         (*p_last
-                            - (v_to_end
-                                * length
-                                * app.duration.since_prev_update.as_secs_f32()
-                                * config.get_with_default("step_amount", 8.0)
+                            - (v_to_end * length * dt.as_secs_f32() * genes.step_amount(config)
                                 + p_random))
                             .into(),
+                        rng,
                     )
                 };
 
+                let d_next = p_next.0.distance(self.end);
+                if d_next < self.closest {
+                    self.closest = d_next;
+                    self.steps_since_progress = 0;
+                } else {
+                    self.steps_since_progress += 1;
+                }
+
                 self.points.push(p_next);
+
+                if arrived {
+                    self.finish(LineState::Arrived, config);
+                }
             }
         }
     }
 
-    pub fn draw(&self, draw: &Draw, colour: &Srgba, amount: f32) {
+    pub(crate) fn finish(&mut self, state: LineState, config: &Config) {
+        self.state = state;
+        self.simplify(config.get_with_default("simplify_tolerance", 0.5));
+    }
+
+    /// Drop the points that don't change the shape of the line by more than `tolerance` pixels.
+    pub fn simplify(&mut self, tolerance: f32) {
+        self.points = douglas_peucker(&self.points, tolerance, |p| p.0);
+    }
+
+    /// The points as they should be drawn or exported, varied by `amount` and then smoothed.
+    pub fn draw_points(&self, amount: f32, smoothing: &Smoothing) -> Vec<Point2> {
         let points: Vec<Point2> = self
             .points
             .iter()
@@ -202,9 +325,22 @@ impl Line {
             // .map(|p| (*p + (rand_normalised_vec() * 2.0)).into())
             .collect();
 
+        smoothing.apply(points)
+    }
+
+    /// The growth's colour, faded if the line gave up.
+    pub fn draw_colour(&self, colour: &Srgba) -> Srgba {
+        let mut colour = *colour;
+        colour.alpha *= self.state.alpha_factor();
+        colour
+    }
+
+    pub fn draw(&self, draw: &Draw, colour: &Srgba, amount: f32, smoothing: &Smoothing) {
+        let points = self.draw_points(amount, smoothing);
+
         draw.polyline()
-            .weight(3.0)
-            .color(colour.clone())
+            .weight(LINE_WEIGHT)
+            .color(self.draw_colour(colour))
             .points(points);
     }
 }
@@ -1,14 +1,51 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 // use crate::helpers::{cycle_value_over_time, FrameCapture};
-use nannou::{color::rgb_u32, lyon::geom::euclid::default, prelude::*};
+use nannou::prelude::*;
 use rand::prelude::*;
-
-use crate::helpers::*;
-
-use self::growth::Growth;
-
-mod growth;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::{cycle_curve_over_time, Curve};
+use crate::capture::{sink_for_path, stream_sink_for_path, Recorder, StreamFormat};
+use crate::cli::Args;
+use crate::metadata::CaptureInfo;
+use crate::palette::{Palette, Palettes};
+use crate::raster::growth_polylines;
+use crate::smoothing::{Smoothing, SmoothingMode};
+
+use self::breeding::{cell_at, Breeding, Genome};
+use self::clock::{Clock, TICK};
+use self::growth::{Growth, LineState};
+use self::placement::{Placement, PlacementFiles};
+use self::resize::{rescale_point, ResizeMode};
+use self::simulation::Simulation;
+use self::snapshot::{
+    latest_snapshot_path, lineages_dir, new_snapshot_path, recordings_dir, renders_dir,
+    snapshots_dir, Snapshot,
+};
+use self::species::{choose_species, competing, Species};
+use self::timeline::Timeline;
+use self::topology::{Topology, TopologyMode};
+
+pub mod breeding;
+pub mod clock;
+pub mod growth;
+pub mod placement;
+pub mod render;
+pub mod resize;
+pub mod simulation;
+pub mod snapshot;
+pub mod species;
+pub mod sweep;
+pub mod timeline;
+pub mod topology;
 #[allow(dead_code)]
 mod unused;
 
@@ -21,24 +58,21 @@ pub const STEP_LENGTH: f32 = 10.0;
 pub const FPS: u64 = 5;
 
 pub const TRANSPARENT_BLANCHED_ALMOND: (f32, f32, f32, f32) = (255.0, 235.0, 205.0, 0.000001);
-pub const FRENCH_GREY: u32 = 0xC6BCC8;
-pub const PINK_LAVENDAR: u32 = 0xD7ACCC;
-pub const THISTLE: u32 = 0xD1B3BD;
-pub const CHAMPAGNE: u32 = 0xFCE5C5;
-pub const TEA_GREEN: u32 = 0xC5DCBC;
-pub const COLOURS: [u32; 5] = [FRENCH_GREY, PINK_LAVENDAR, THISTLE, TEA_GREEN, CHAMPAGNE];
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "ConfigFields")]
 pub struct Config {
     // max_vary_amount: f32,
     // step_amount: f32,
     // rand_factor: f32,
     values: HashMap<String, f32>,
     keys: Vec<String>,
+    #[serde(skip)]
     selected_value: usize,
 }
 
 impl Config {
-    fn new(values: HashMap<String, f32>) -> Self {
+    pub fn new(values: HashMap<String, f32>) -> Self {
         let keys: Vec<String> = values.keys().cloned().collect();
         Self {
             values,
@@ -47,14 +81,44 @@ impl Config {
         }
     }
 
+    /// The default config with any values from a preset file of `key = value` lines, which can
+    /// have `//` comments.
+    pub fn load_preset(path: &Path) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut config = Config::default();
+
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("line {}: expected `key = value`", i + 1)))?;
+            let value = value.trim();
+            config.set(
+                key.trim(),
+                value
+                    .parse()
+                    .map_err(|_| invalid(format!("line {}: bad value `{value}`", i + 1)))?,
+            );
+        }
+
+        Ok(config)
+    }
+
     pub fn get_with_default(&self, key: &str, default: f32) -> f32 {
         *self.values.get(key).unwrap_or(&default)
     }
 
-    pub fn next_key(&mut self) {
-        
+    /// Every key and its value, in the order they're listed.
+    pub fn values(&self) -> impl Iterator<Item = (&str, f32)> + '_ {
+        self.keys.iter().map(|key| (key.as_str(), self.values[key]))
+    }
 
-        if self.selected_value == self.keys.len() -1 {
+    pub fn next_key(&mut self) {
+        if self.selected_value == self.keys.len() - 1 {
             self.selected_value = 0;
         } else {
             self.selected_value += 1;
@@ -62,8 +126,6 @@ impl Config {
     }
 
     pub fn prev_key(&mut self) {
-        
-
         if self.selected_value == 0 {
             self.selected_value = self.keys.len() - 1;
         } else {
@@ -71,43 +133,210 @@ impl Config {
         };
     }
 
+    /// The smoothing stage for `mode`, with its settings from the config.
+    pub fn smoothing(&self, mode: SmoothingMode) -> Smoothing {
+        match mode {
+            SmoothingMode::None => Smoothing::None,
+            SmoothingMode::Chaikin => Smoothing::Chaikin {
+                iterations: self
+                    .get_with_default("chaikin_iterations", 2.0)
+                    .clamp(0.0, 6.0) as u32,
+            },
+            SmoothingMode::CatmullRom => Smoothing::CatmullRom {
+                spacing: self.get_with_default("smoothing_spacing", 4.0).max(0.5),
+            },
+        }
+    }
+
+    /// The topology for `mode`, with its settings from the config.
+    pub fn topology(&self, mode: TopologyMode) -> Topology {
+        match mode {
+            TopologyMode::Complete => Topology::Complete,
+            TopologyMode::Nearest => Topology::Nearest {
+                k: self.get_with_default("neighbours", 3.0).max(1.0) as usize,
+            },
+            TopologyMode::Delaunay => Topology::Delaunay,
+            TopologyMode::Gabriel => Topology::Gabriel,
+            TopologyMode::SpanningTree => Topology::SpanningTree,
+            TopologyMode::Random => Topology::Random {
+                p: self
+                    .get_with_default("link_probability", 0.2)
+                    .clamp(0.0, 1.0),
+            },
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: f32) {
+        if !self.values.contains_key(key) {
+            self.keys.push(key.to_string());
+        }
+        self.values.insert(key.to_string(), value);
+    }
+
     pub fn change_value(&mut self, amount: f32) {
         let key = &self.keys[self.selected_value];
-        let mut value = self.values.get_mut(key).unwrap();
+        let value = self.values.get_mut(key).unwrap();
         *value += amount;
     }
 }
+
+/// A [`Config`] as saved in snapshots and lineages, which might not list exactly the keys it has
+/// values for.
+#[derive(Deserialize)]
+struct ConfigFields {
+    values: HashMap<String, f32>,
+    keys: Vec<String>,
+}
+
+impl From<ConfigFields> for Config {
+    /// Keys without a value are dropped and values without a key are listed after the rest, so
+    /// every key always has a value.
+    fn from(ConfigFields { values, mut keys }: ConfigFields) -> Self {
+        let mut seen = HashSet::new();
+        keys.retain(|key| values.contains_key(key) && seen.insert(key.clone()));
+        let mut unlisted: Vec<String> = values
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .cloned()
+            .collect();
+        unlisted.sort();
+        keys.extend(unlisted);
+
+        Config {
+            values,
+            keys,
+            selected_value: 0,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new(HashMap::from([
+            ("max_vary_amount".to_string(), 8.0),
+            ("step_amount".to_string(), 8.0),
+            ("rand_factor".to_string(), 1.5),
+            ("smoothing_spacing".to_string(), 4.0),
+            ("chaikin_iterations".to_string(), 2.0),
+            ("simplify_tolerance".to_string(), 0.5),
+            ("max_steps".to_string(), 10000.0),
+            ("stuck_steps".to_string(), 600.0),
+            ("recording_fps".to_string(), 30.0),
+            ("mutation_rate".to_string(), 0.2),
+            ("competition".to_string(), 0.0),
+            ("territory_size".to_string(), 12.0),
+            ("neighbours".to_string(), 3.0),
+            ("link_probability".to_string(), 0.2),
+            ("min_distance".to_string(), 120.0),
+            ("grid_jitter".to_string(), 0.5),
+            ("placement_radius".to_string(), 0.8),
+        ]))
+    }
+}
+
 struct Model {
-    growths: Vec<Growth>,
+    /// Everything growing in the main window, which the window only steps and draws.
+    simulation: Simulation,
     // frame_capture: FrameCapture,
     main_window_id: WindowId,
-    config_window_id: WindowId,
-    vary_amount: f32,
-    config: Config,
+    palettes: Palettes,
+    resize_mode: ResizeMode,
+    clock: Clock,
+    recorder: Option<Recorder>,
+    /// Set while picking between mutated configs instead of running one simulation.
+    breeding: Option<Breeding>,
 }
 
 impl Model {
-    fn new_from_app(app: &App, main_window_id: WindowId, config_window_id: WindowId) -> Self {
-        let growths: Vec<Growth> = create_new_growths(app, main_window_id, NUM_GROWTHS);
+    fn new_from_app(
+        app: &App,
+        main_window_id: WindowId,
+        palettes: Palettes,
+        species: Vec<Species>,
+        timeline: Timeline,
+        seed: u64,
+    ) -> Self {
+        let world = app.window(main_window_id).unwrap().rect();
+        let simulation = Simulation::new(
+            seed,
+            world,
+            Config::default(),
+            palettes.current().clone(),
+            timeline,
+        )
+        .with_species(species)
+        .start();
 
         Model {
-            growths,
-            // frame_capture: FrameCapture::new_from_app_with_seed(app, &rand_seed.to_string()),
+            simulation,
+            // frame_capture: FrameCapture::new_from_app_with_seed(app, "mycelium", &rand_seed.to_string()),
             main_window_id,
-            config_window_id,
-            vary_amount: 1.0,
-            config: Config::new(HashMap::from([
-                ("max_vary_amount".to_string(), 8.0),
-                ("step_amount".to_string(), 8.0),
-                ("rand_factor".to_string(), 1.5),
-            ])),
+            palettes,
+            resize_mode: ResizeMode::default(),
+            clock: Clock::default(),
+            recorder: None,
+            breeding: None,
+        }
+    }
+
+    /// Start a new simulation of `seed` with `config`, keeping what was loaded from the project
+    /// folder and how growths are placed and linked, and rewinding the timeline.
+    fn start_over(&mut self, seed: u64, config: Config) {
+        let simulation = &mut self.simulation;
+        let mut timeline = std::mem::take(&mut simulation.timeline);
+        timeline.seek_to(Duration::ZERO);
+        self.simulation = Simulation::new(
+            seed,
+            simulation.world,
+            config,
+            self.palettes.current().clone(),
+            timeline,
+        )
+        .with_species(std::mem::take(&mut simulation.species))
+        .with_topology(simulation.topology_mode)
+        .with_placement(
+            simulation.placement_mode,
+            std::mem::take(&mut simulation.placement_files),
+        )
+        .start();
+    }
+
+    fn capture_info(&self, app: &App) -> CaptureInfo {
+        let window_rect = app.window(self.main_window_id).unwrap().rect();
+        let simulation = &self.simulation;
+        let mut info = CaptureInfo::new(
+            "mycelium",
+            simulation.seed,
+            &simulation.config,
+            (window_rect.w() as u32, window_rect.h() as u32),
+        );
+        info.palette = simulation.palette.name.clone();
+        info.smoothing = simulation.smoothing_mode.name().to_string();
+        info.topology = simulation.topology_mode.name().to_string();
+        info.placement = simulation.placement_mode.name().to_string();
+        if !simulation.timeline.is_empty() {
+            info.timeline = Some("timeline.txt".to_string());
+        }
+        if simulation.species != [Species::default()] {
+            info.species = Some("species.txt".to_string());
+        }
+        info
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        if self.palettes.select(&snapshot.palette) {
+            self.simulation.palette = self.palettes.current().clone();
+        } else {
+            eprintln!("palette `{}` isn't loaded", snapshot.palette);
         }
+        self.simulation.restore(snapshot);
     }
 }
 
 pub fn main() {
     nannou::app(model)
         .update(update)
+        .exit(exit)
         .loop_mode(LoopMode::RefreshSync)
         .run();
 }
@@ -119,11 +348,12 @@ fn model(app: &App) -> Model {
         .size(WINDOW_SIZE as u32, WINDOW_SIZE as u32)
         .view(main_view)
         .key_pressed(key_pressed)
+        .mouse_pressed(mouse_pressed)
+        .resized(resized)
         .build()
         .unwrap();
 
-    let config_window_id = app
-        .new_window()
+    app.new_window()
         .title("Config")
         .size(WINDOW_SIZE as u32, WINDOW_SIZE as u32)
         .view(config_view)
@@ -132,55 +362,334 @@ fn model(app: &App) -> Model {
         .unwrap();
 
     app.set_fullscreen_on_shortcut(true);
-    let model = Model::new_from_app(app, main_window_id, config_window_id);
+    let mut model = Model::new_from_app(
+        app,
+        main_window_id,
+        Palettes::load_from_app(app),
+        Species::load_from_app(app),
+        Timeline::load_from_app(app),
+        random(),
+    );
+
+    model.simulation.placement_files = PlacementFiles::load_from_app(app);
+    match Args::from_env().get::<ResizeMode>("resize") {
+        Ok(mode) => model.resize_mode = mode.unwrap_or_default(),
+        Err(e) => eprintln!("{e}"),
+    }
+
+    if let Err(e) = stream_from_start(app, &mut model) {
+        eprintln!("{e}");
+    }
     model
 }
 
+/// `--stream <file, named pipe or -> [--stream-format rgba|y4m]` records from the first frame, see
+/// [`sink_for_path`] for the formats picked without `--stream-format`.
+fn stream_from_start(app: &App, model: &mut Model) -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if let Some(target) = args.get::<PathBuf>("stream")? {
+        start_recording(app, model, &target, args.get("stream-format")?);
+    }
+    Ok(())
+}
+
 fn update(app: &App, model: &mut Model, _update: Update) {
+    let ticks = match model.clock.tick(app.duration.since_prev_update) {
+        Some(ticks) => ticks,
+        None => return,
+    };
+
+    if let Some(breeding) = &mut model.breeding {
+        for _ in 0..ticks {
+            breeding.step(TICK);
+        }
+        return;
+    }
+
     // if app.elapsed_frames() % FPS != 0 {
     //     return;
     // }
     // model.step_circles(app.duration.since_start);
-    model.growths = step_growths(&model, app);
-    // model.lines = move_lines(&model);
+    model.simulation.step_ticks(ticks);
+
+    if let Some(recorder) = &mut model.recorder {
+        let simulation = &model.simulation;
+        let smoothing = simulation.config.smoothing(simulation.smoothing_mode);
+        let mut polylines =
+            growth_polylines(&simulation.growths, simulation.vary_amount, &smoothing);
+        // recordings keep the size they started at, whatever happens to the window
+        if recorder.world() != simulation.world {
+            for point in polylines.iter_mut().flat_map(|(points, _)| points) {
+                *point = rescale_point(*point, simulation.world, recorder.world());
+            }
+        }
+        if recorder.record(polylines).is_err() {
+            stop_recording(model);
+        }
+    }
+}
 
-    model.growths = change_points(&model, &app);
+/// Record the main window at `recording_fps` to `path`, as a stream of `format` if one is given,
+/// stepping the simulation by exactly one frame's worth of time per update until recording stops.
+fn start_recording(app: &App, model: &mut Model, path: &Path, format: Option<StreamFormat>) {
+    let fps = model
+        .simulation
+        .config
+        .get_with_default("recording_fps", 30.0)
+        .max(1.0) as u32;
+    let window_rect = app.window(model.main_window_id).unwrap().rect();
+    let sink = match format {
+        Some(format) => stream_sink_for_path(path, format, fps),
+        None => sink_for_path(path, fps),
+    };
+
+    match sink {
+        Ok(mut sink) => {
+            let mut info = model.capture_info(app);
+            info.fps = Some(fps);
+            sink.set_text_chunks(info.text_chunks());
+            if path != Path::new("-") {
+                let manifest_path = path.with_extension("json");
+                if let Err(e) = info.write_manifest(&manifest_path) {
+                    eprintln!("failed to write {}: {e}", manifest_path.display());
+                }
+            }
+
+            let recorder = Recorder::start(
+                sink,
+                fps,
+                window_rect.w() as u32,
+                window_rect.h() as u32,
+                window_rect,
+                model.palettes.current().background,
+            );
+            model.clock.set_fixed_dt(Some(recorder.frame_duration()));
+            model.recorder = Some(recorder);
+            eprintln!("recording to {}", path.display());
+        }
+        Err(e) => eprintln!("failed to record to {}: {e}", path.display()),
+    }
+}
 
-    model.vary_amount = cycle_value_over_time(
-        app.duration.since_start,
-        Duration::from_secs(12),
-        1.0,
-        model.config.get_with_default("max_vary_amount", 1.5),
-    )
+fn stop_recording(model: &mut Model) {
+    model.clock.set_fixed_dt(None);
+    if let Some(recorder) = model.recorder.take() {
+        // encoding the rest can take a while, so keep the sketch running meanwhile
+        std::thread::spawn(move || finish_recording(recorder));
+    }
+}
+
+fn finish_recording(recorder: Recorder) {
+    let num_frames = recorder.num_frames();
+    match recorder.finish() {
+        Ok(()) => eprintln!("recorded {num_frames} frames"),
+        Err(e) => eprintln!("failed to record: {e}"),
+    }
+}
+
+/// Finish any recording before the sketch closes, rather than on a thread that wouldn't get to.
+fn exit(_app: &App, model: Model) {
+    if let Some(recorder) = model.recorder {
+        finish_recording(recorder);
+    }
+}
+
+/// Start breeding from the current config and seed, or stop and carry on with the last chosen.
+fn toggle_breeding(app: &App, model: &mut Model) {
+    match model.breeding.take() {
+        Some(breeding) => {
+            let parent = breeding.parent().clone();
+            model.start_over(parent.seed, parent.config);
+            if let Some(path) = breeding.lineage_path() {
+                eprintln!(
+                    "bred for {} generations, lineage saved to {}",
+                    breeding.generation,
+                    path.display()
+                );
+            }
+        }
+        None => {
+            let simulation = &mut model.simulation;
+            let parent = Genome {
+                seed: simulation.seed,
+                config: simulation.config.clone(),
+            };
+            model.breeding = Some(Breeding::new(
+                parent,
+                simulation.config.get_with_default("mutation_rate", 0.2),
+                app.window(model.main_window_id).unwrap().rect(),
+                model.palettes.current().clone(),
+                simulation.rng().gen(),
+                Some(new_snapshot_path(&lineages_dir(app))),
+            ));
+        }
+    }
+}
+
+/// Clicking a cell while breeding makes it the parent of the next generation.
+fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    let Some(breeding) = &mut model.breeding else {
+        return;
+    };
+    if button != MouseButton::Left {
+        return;
+    }
+
+    let window_rect = app.window(model.main_window_id).unwrap().rect();
+    if let Some(cell) = cell_at(window_rect, app.mouse.position()) {
+        let rate = model
+            .simulation
+            .config
+            .get_with_default("mutation_rate", 0.2);
+        breeding.choose(cell, rate);
+    }
+}
+
+/// Keep the growths filling the main window, by [`ResizeMode`].
+fn resized(_app: &App, model: &mut Model, size: Vec2) {
+    let world = Rect::from_wh(size);
+    if size.x <= 0.0 || size.y <= 0.0 || world == model.simulation.world {
+        return;
+    }
+    model.simulation.resize(world, model.resize_mode);
 }
 
 fn key_pressed(app: &App, model: &mut Model, key: Key) {
     if key == Key::Return {
-        *model = Model::new_from_app(app, model.main_window_id, model.config_window_id);
+        model.start_over(random(), Config::default());
+    }
+
+    if key == Key::B {
+        toggle_breeding(app, model);
+    }
+
+    if key == Key::V {
+        if model.recorder.is_some() {
+            stop_recording(model);
+        } else {
+            let extension = if app.keys.mods.shift() { "gif" } else { "png" };
+            let path = new_snapshot_path(&recordings_dir(app)).with_extension(extension);
+            start_recording(app, model, &path, None);
+        }
+    }
+
+    if key == Key::F5 {
+        let path = new_snapshot_path(&snapshots_dir(app));
+        match model.simulation.snapshot().save(&path) {
+            Ok(()) => eprintln!("saved snapshot to {}", path.display()),
+            Err(e) => eprintln!("failed to save snapshot to {}: {e}", path.display()),
+        }
+    }
+
+    if key == Key::F12 {
+        let snapshot = model.simulation.snapshot();
+        let path = new_snapshot_path(&renders_dir(app)).with_extension("png");
+        // rendering this big takes a while, so keep the sketch running meanwhile
+        std::thread::spawn(move || {
+            match render::render_snapshot(&snapshot, render::RENDER_SIZE, None, &path) {
+                Ok(()) => eprintln!("rendered {}", path.display()),
+                Err(e) => eprintln!("failed to render {}: {e}", path.display()),
+            }
+        });
+    }
+
+    if key == Key::F9 {
+        match latest_snapshot_path(&snapshots_dir(app)) {
+            Some(path) => match Snapshot::load(&path) {
+                Ok(snapshot) => model.restore(snapshot),
+                Err(e) => eprintln!("failed to load snapshot {}: {e}", path.display()),
+            },
+            None => eprintln!("no snapshots to load"),
+        }
+    }
+
+    if key == Key::Space {
+        model.clock.toggle_paused();
+    }
+
+    if key == Key::Period {
+        model.clock.request_step();
+    }
+
+    if key == Key::Equals || key == Key::NumpadAdd {
+        model.clock.faster();
+    }
+
+    if key == Key::Minus || key == Key::NumpadSubtract {
+        model.clock.slower();
+    }
+
+    let simulation = &mut model.simulation;
+
+    if key == Key::T {
+        simulation.timeline.toggle_playing();
+    }
+
+    if key == Key::LBracket {
+        simulation.timeline.seek_by(-5.0);
+    }
+
+    if key == Key::RBracket {
+        simulation.timeline.seek_by(5.0);
+    }
+
+    if key == Key::Home {
+        simulation.timeline.seek_to(Duration::ZERO);
+    }
+
+    if key == Key::E {
+        simulation.vary_curve = simulation.vary_curve.next_curve();
+    }
+
+    if key == Key::S {
+        simulation.smoothing_mode = simulation.smoothing_mode.next_mode();
+    }
+
+    if key == Key::G {
+        simulation.topology_mode = simulation.topology_mode.next_mode();
+        simulation.regrow();
+    }
+
+    if key == Key::R {
+        model.resize_mode = model.resize_mode.next_mode();
+    }
+
+    if key == Key::C {
+        // skip the file modes without a file to place from
+        simulation.placement_mode = simulation.placement_mode.next_mode();
+        while !simulation.placement_files.has(simulation.placement_mode) {
+            simulation.placement_mode = simulation.placement_mode.next_mode();
+        }
+        simulation.regrow();
+    }
+
+    if key == Key::P {
+        let palette = model.palettes.next_palette().clone();
+        simulation.set_palette(palette, &mut thread_rng());
     }
 
     if key == Key::Left {
-        model.config.prev_key()
+        simulation.config.prev_key()
     }
 
     if key == Key::Right {
-        model.config.next_key()
+        simulation.config.next_key()
     }
 
     if key == Key::Up {
-        model.config.change_value(0.1)
+        simulation.config.change_value(0.1)
     }
 
     if key == Key::Down {
-        model.config.change_value(-0.1)
+        simulation.config.change_value(-0.1)
     }
 
     if key == Key::PageUp {
-        model.config.change_value(1.0)
+        simulation.config.change_value(1.0)
     }
 
     if key == Key::PageDown {
-        model.config.change_value(-1.0)
+        simulation.config.change_value(-1.0)
     }
 }
 
@@ -190,12 +699,16 @@ fn main_view(app: &App, model: &Model, frame: Frame) {
     // }
     // let draw = app.draw().xy(model.starting_point);
     let draw = app.draw();
-    draw.background().color(BLACK);
+    draw.background().color(model.palettes.current().background);
 
-    for growth in &model.growths {
-        growth.draw(&draw, model.vary_amount)
+    if let Some(breeding) = &model.breeding {
+        breeding.draw(&draw, app.window(model.main_window_id).unwrap().rect());
+        draw.to_frame(app, &frame).unwrap();
+        return;
     }
 
+    model.simulation.draw(&draw);
+
     // let fps = app.fps();
     // let num_growths = model.growths.len();
     // let finished = model
@@ -222,113 +735,259 @@ fn main_view(app: &App, model: &Model, frame: Frame) {
     // }
 }
 
-fn step_growths(model: &Model, app: &App) -> Vec<Growth> {
-    let mut growths = model.growths.clone();
+/// Step every growth, spread across a thread pool. Each line has its own rng so the result is the
+/// same as [`step_growths_serial`] for a given seed.
+pub fn step_growths(growths: &mut [Growth], dt: Duration, config: &Config) {
+    growths
+        .par_iter_mut()
+        .for_each(|g| g.par_step_growth(dt, config));
+}
 
-    for g in &mut growths {
-        g.step_growth(app, &model.config)
+pub fn step_growths_serial(growths: &mut [Growth], dt: Duration, config: &Config) {
+    for g in growths {
+        g.step_growth(dt, config)
     }
+}
 
+/// Whether it's time for new growths, which is once 2/3 are finished.
+pub fn mostly_finished(growths: &[Growth]) -> bool {
     growths
+        .iter()
+        .fold(0, |acc, g| acc + if g.is_finished() { 1 } else { 0 })
+        >= growths.len() * 2 / 3
 }
 
-fn change_points(model: &Model, app: &App) -> Vec<Growth> {
-    // let mut starting_points = model.starting_points.clone();
-    // let mut lines = model.lines.clone();
-
-    // remove any finished growths
-    // let mut growths: Vec<Growth> = model
-    //     .growths
-    //     .clone()
-    //     .into_iter()
-    //     .filter(|growth| !growth.is_finished())
-    //     .collect();
-
-    // // add a new growth for every removed one
-    // let num_removed_growths = NUM_GROWTHS as usize - growths.len();
-    // if num_removed_growths != 0 {
-    //     let window_rect = app.window_rect();
-    //     let mut rng = thread_rng();
-    //     let centre_points: Vec<Vec2> = growths.iter().map(|g| g.centre).collect();
-
-    //     let mut new_growths = (0..num_removed_growths)
-    //         .map(|_| {
-    //             let centre = vec2(
-    //                 rng.gen_range(window_rect.x.start - 50.0 ..window_rect.x.end + 50.0),
-    //                 rng.gen_range(window_rect.y.start - 50.0..window_rect.y.end - 50.0),
-    //             );
-
-    //             Growth::new(
-    //                 centre,
-    //                 &centre_points,
-    //                 rgb_u32(COLOURS[rng.gen_range(0..COLOURS.len() - 1)]).into(),
-    //             )
-    //         })
-    //         .collect();
-
-    //     growths.append(&mut new_growths);
-    // }
+/// How much lines wander from their path `elapsed` into the simulation.
+pub fn vary_amount_at(curve: &Curve, elapsed: Duration, config: &Config) -> f32 {
+    cycle_curve_over_time(
+        curve,
+        elapsed,
+        Duration::from_secs(12),
+        0.0,
+        1.0,
+        config.get_with_default("max_vary_amount", 1.5),
+    )
+}
 
-    // finished if 2/3 are finished
-    let is_finished = model
-        .growths
-        .iter()
-        .fold(0, |acc, g| acc + if g.is_finished() { 1 } else { 0 })
-        >= model.growths.len() * 2 / 3;
+pub fn create_new_growths<R: Rng>(
+    window_rect: Rect,
+    num_growths: u64,
+    palette: &Palette,
+    rng: &mut R,
+) -> Vec<Growth> {
+    create_new_colonies(
+        window_rect,
+        num_growths,
+        palette,
+        &[Species::default()],
+        &Placement::Uniform { padding: 50.0 },
+        &Topology::Complete,
+        rng,
+    )
+}
 
-    if is_finished {
-        return create_new_growths(app, model.main_window_id, NUM_GROWTHS);
+/// Growths of any of `species`, picked by weight, placed by `placement` and linked by `topology`.
+/// A single species with the default genes, placed uniformly and completely linked, grows exactly
+/// like [`create_new_growths`].
+pub fn create_new_colonies<R: Rng>(
+    window_rect: Rect,
+    num_growths: u64,
+    palette: &Palette,
+    species: &[Species],
+    placement: &Placement,
+    topology: &Topology,
+    rng: &mut R,
+) -> Vec<Growth> {
+    let default_species = [Species::default()];
+    let species = if species.is_empty() {
+        &default_species[..]
     } else {
-        return model.growths.clone();
-    }
-}
+        species
+    };
 
-fn create_new_growths(app: &App, window_id: WindowId, num_growths: u64) -> Vec<Growth> {
-    let mut rng = thread_rng();
-    let window_rect = app.window(window_id).unwrap().rect();
-    let centre_points: Vec<Point2> = (0..num_growths)
-        .map(|_| {
-            vec2(
-                rng.gen_range(window_rect.x.start - 50.0..window_rect.x.end + 50.0),
-                rng.gen_range(window_rect.y.start - 50.0..window_rect.y.end + 50.0),
-            )
-        })
-        .collect();
+    let centre_points = placement.centres(window_rect, num_growths, rng);
+    let links = topology.links(&centre_points, rng);
 
-    let growths: Vec<Growth> = (&centre_points)
+    centre_points
         .iter()
-        .map(|p_c| {
-            Growth::new(
-                *p_c,
-                &centre_points,
-                rgb_u32(rand_from_slice(&COLOURS)).into(),
-            )
+        .zip(links)
+        .map(|(p_c, links)| {
+            let index = choose_species(species, rng);
+            let species = &species[index];
+            let colour = species.colour(palette, rng);
+            let branching = species.genes.branching;
+            let linked: Vec<Point2> = links
+                .into_iter()
+                .filter(|_| branching >= 1.0 || rng.gen::<f32>() < branching)
+                .map(|i| centre_points[i])
+                .collect();
+            Growth::new(*p_c, &linked, colour, rng).with_species(index, species.genes)
         })
-        .collect();
-    growths
+        .collect()
 }
 
 fn config_view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
     draw.background().color(WHEAT);
 
-    let width = 500.0;
-    let step = width / model.config.keys.len() as f32;
+    let simulation = &model.simulation;
+    let window_rect = app.window_rect();
+    let row_height = 24.0;
+    let left = window_rect.left() + 40.0;
+    let top = window_rect.top() - 40.0;
 
-    for (i, key) in model.config.keys.iter().enumerate() {
-        let value = model.config.values.get(key).unwrap();
-        let x = -(width / 2.0) + step * i as f32;
-        let colour = if i == model.config.selected_value {
+    for (i, key) in simulation.config.keys.iter().enumerate() {
+        let value = simulation.config.values.get(key).unwrap();
+        let y = top - row_height * i as f32;
+        let colour = if i == simulation.config.selected_value {
             BLUEVIOLET
         } else {
             BLACK
         };
-        draw.x_y(x, -50.0).text(&key).font_size(16).color(colour);
-        draw.x_y(x, 0.0)
-            .text(&format!("{value}"))
+        draw.text(key)
+            .x_y(left + 100.0, y)
+            .w(200.0)
+            .left_justify()
+            .font_size(16)
+            .color(colour);
+        draw.text(&format!("{value}"))
+            .x_y(left + 300.0, y)
+            .w(200.0)
+            .left_justify()
             .font_size(16)
             .color(colour);
     }
 
+    let lines = simulation.growths.iter().flat_map(|g| &g.lines);
+    let count = |state: LineState| lines.clone().filter(|l| l.state == state).count();
+    let status = [
+        format!("palette: {}", simulation.palette.name),
+        format!("smoothing: {}", simulation.smoothing_mode.name()),
+        format!("topology: {}", simulation.topology_mode.name()),
+        format!("placement: {}", simulation.placement_mode.name()),
+        format!("on resize: {}", model.resize_mode.name()),
+        format!("vary curve: {}", simulation.vary_curve.name()),
+        format!("seed: {}", simulation.seed),
+        format!(
+            "clock: {} at {}x, {:.1}s",
+            if model.clock.is_paused() {
+                "paused"
+            } else {
+                "running"
+            },
+            model.clock.time_scale(),
+            simulation.elapsed().as_secs_f32()
+        ),
+        match &model.recorder {
+            Some(recorder) => format!("recording: {} frames", recorder.num_frames()),
+            None => "recording: off".to_string(),
+        },
+        species_status(simulation),
+        match &model.breeding {
+            Some(breeding) => format!("breeding: generation {}", breeding.generation),
+            None => "breeding: off".to_string(),
+        },
+        format!(
+            "lines: {} growing, {} arrived, {} stuck, {} abandoned{}",
+            count(LineState::Growing),
+            count(LineState::Arrived),
+            count(LineState::Stuck),
+            count(LineState::Abandoned),
+            if competing(&simulation.config) {
+                format!(", {} dormant", count(LineState::Dormant))
+            } else {
+                String::new()
+            },
+        ),
+    ];
+
+    let status_top = top - row_height * (simulation.config.keys.len() + 1) as f32;
+    for (i, text) in status.iter().enumerate() {
+        draw.text(text)
+            .x_y(left + 400.0, status_top - row_height * i as f32)
+            .w(800.0)
+            .left_justify()
+            .font_size(16)
+            .color(BLACK);
+    }
+
+    if !simulation.timeline.is_empty() {
+        draw_timeline_strip(&draw, &simulation.timeline, window_rect);
+    }
+
     draw.to_frame(app, &frame).unwrap();
 }
+
+/// The species on the canvas, and how much of it each holds while they compete.
+fn species_status(simulation: &Simulation) -> String {
+    let names: Vec<String> = simulation
+        .species
+        .iter()
+        .enumerate()
+        .map(|(i, species)| {
+            if competing(&simulation.config) {
+                format!("{} ({})", species.name, simulation.territory().claimed(i))
+            } else {
+                species.name.clone()
+            }
+        })
+        .collect();
+    format!("species: {}", names.join(", "))
+}
+
+/// A bar along the bottom of the config window with a row of keyframes per track and the playhead.
+fn draw_timeline_strip(draw: &Draw, timeline: &Timeline, window_rect: Rect) {
+    let row_height = 20.0;
+    let num_tracks = timeline.tracks().count();
+    let strip = Rect::from_x_y_w_h(
+        0.0,
+        window_rect.bottom() + 40.0 + row_height * num_tracks as f32 / 2.0,
+        window_rect.w() - 300.0,
+        row_height * num_tracks as f32,
+    )
+    .shift_x(100.0);
+    let duration = timeline.duration().as_secs_f32().max(1.0);
+    let x_at = |time: Duration| strip.left() + strip.w() * (time.as_secs_f32() / duration).min(1.0);
+
+    draw.rect()
+        .xy(strip.xy())
+        .wh(strip.wh())
+        .color(BLANCHEDALMOND);
+
+    for (i, (key, keyframes)) in timeline.tracks().enumerate() {
+        let y = strip.top() - row_height * (i as f32 + 0.5);
+        draw.text(key)
+            .x_y(strip.left() - 110.0, y)
+            .w(200.0)
+            .right_justify()
+            .font_size(12)
+            .color(BLACK);
+        for keyframe in keyframes {
+            draw.ellipse()
+                .x_y(x_at(keyframe.time), y)
+                .radius(4.0)
+                .color(BLUEVIOLET);
+        }
+    }
+
+    let playhead_x = x_at(timeline.playhead());
+    draw.line()
+        .start(pt2(playhead_x, strip.bottom()))
+        .end(pt2(playhead_x, strip.top()))
+        .weight(2.0)
+        .color(BLACK);
+    draw.text(&format!(
+        "timeline {:.1}s / {:.1}s {}",
+        timeline.playhead().as_secs_f32(),
+        duration,
+        if timeline.is_playing() {
+            "playing"
+        } else {
+            "paused"
+        }
+    ))
+    .x_y(strip.x(), strip.top() + 14.0)
+    .w(strip.w())
+    .font_size(14)
+    .color(BLACK);
+}
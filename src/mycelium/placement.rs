@@ -0,0 +1,362 @@
+//! Where growth centres go.
+//!
+//! Centres can also come from files in the project folder: `centres.csv` (an `x, y` pair per line)
+//! or `centres.json` (a list of `[x, y]` pairs or `{"x": .., "y": ..}` objects), with coordinates as
+//! fractions of the window from its top left corner, and `centres.png` (or `.jpg`), whose brighter
+//! parts get more centres.
+
+use std::{fs, io, path::Path, str::FromStr, sync::Arc};
+
+use nannou::{image::GrayImage, prelude::*};
+use rand::prelude::*;
+use serde::Deserialize;
+
+use super::Config;
+
+/// The kinds of [`Placement`], which the sketch cycles through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlacementMode {
+    #[default]
+    Uniform,
+    PoissonDisk,
+    JitteredGrid,
+    Ring,
+    Spiral,
+    File,
+    Image,
+}
+
+impl PlacementMode {
+    pub fn next_mode(self) -> Self {
+        match self {
+            PlacementMode::Uniform => PlacementMode::PoissonDisk,
+            PlacementMode::PoissonDisk => PlacementMode::JitteredGrid,
+            PlacementMode::JitteredGrid => PlacementMode::Ring,
+            PlacementMode::Ring => PlacementMode::Spiral,
+            PlacementMode::Spiral => PlacementMode::File,
+            PlacementMode::File => PlacementMode::Image,
+            PlacementMode::Image => PlacementMode::Uniform,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlacementMode::Uniform => "uniform",
+            PlacementMode::PoissonDisk => "poisson-disk",
+            PlacementMode::JitteredGrid => "jittered-grid",
+            PlacementMode::Ring => "ring",
+            PlacementMode::Spiral => "spiral",
+            PlacementMode::File => "file",
+            PlacementMode::Image => "image",
+        }
+    }
+}
+
+impl FromStr for PlacementMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [
+            PlacementMode::Uniform,
+            PlacementMode::PoissonDisk,
+            PlacementMode::JitteredGrid,
+            PlacementMode::Ring,
+            PlacementMode::Spiral,
+            PlacementMode::File,
+            PlacementMode::Image,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == name)
+        .ok_or_else(|| format!("unknown placement `{name}`"))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Placement {
+    /// Anywhere in the window, or up to `padding` pixels outside it.
+    Uniform { padding: f32 },
+    /// Anywhere in the window, but no closer than `min_distance` to another centre while there's
+    /// room.
+    PoissonDisk { min_distance: f32 },
+    /// One per cell of a grid over the window, moved up to `jitter` of a cell from its middle.
+    JitteredGrid { jitter: f32 },
+    /// Evenly around a circle `radius` of the way from the middle of the window to its nearest edge.
+    Ring { radius: f32 },
+    /// Out from the middle by the golden angle, filling a circle `radius` of the way to the nearest
+    /// edge.
+    Spiral { radius: f32 },
+    /// These points, as fractions of the window from its top left corner, however many centres
+    /// are asked for.
+    Points(Arc<Vec<Point2>>),
+    /// At random, more likely the brighter the image is there.
+    Density(Arc<GrayImage>),
+}
+
+impl Placement {
+    /// `num_centres` centres for a window covering `window_rect`.
+    pub fn centres<R: Rng>(&self, window_rect: Rect, num_centres: u64, rng: &mut R) -> Vec<Point2> {
+        let n = num_centres as usize;
+        match self {
+            Placement::Uniform { padding } => (0..n)
+                .map(|_| {
+                    vec2(
+                        rng.gen_range(window_rect.x.start - padding..window_rect.x.end + padding),
+                        rng.gen_range(window_rect.y.start - padding..window_rect.y.end + padding),
+                    )
+                })
+                .collect(),
+            Placement::PoissonDisk { min_distance } => {
+                poisson_disk(window_rect, n, *min_distance, rng)
+            }
+            Placement::JitteredGrid { jitter } => jittered_grid(window_rect, n, *jitter, rng),
+            Placement::Ring { radius } => {
+                let radius = radius * window_rect.w().min(window_rect.h()) / 2.0;
+                let start = rng.gen_range(0.0..TAU);
+                (0..n)
+                    .map(|i| {
+                        let angle = start + TAU * i as f32 / n as f32;
+                        window_rect.xy() + vec2(angle.cos(), angle.sin()) * radius
+                    })
+                    .collect()
+            }
+            Placement::Spiral { radius } => {
+                let radius = radius * window_rect.w().min(window_rect.h()) / 2.0;
+                let golden_angle = PI * (3.0 - 5.0f32.sqrt());
+                (0..n)
+                    .map(|i| {
+                        let r = radius * ((i as f32 + 0.5) / n as f32).sqrt();
+                        let angle = golden_angle * i as f32;
+                        window_rect.xy() + vec2(angle.cos(), angle.sin()) * r
+                    })
+                    .collect()
+            }
+            Placement::Points(points) => points
+                .iter()
+                .map(|p| {
+                    vec2(
+                        window_rect.left() + p.x * window_rect.w(),
+                        window_rect.top() - p.y * window_rect.h(),
+                    )
+                })
+                .collect(),
+            Placement::Density(image) => density(window_rect, n, image, rng),
+        }
+    }
+}
+
+/// Dart throwing: random points, keeping those far enough from the ones already kept. If the
+/// window fills up first, the closest fit of the remaining tries is taken instead.
+fn poisson_disk<R: Rng>(
+    window_rect: Rect,
+    n: usize,
+    min_distance: f32,
+    rng: &mut R,
+) -> Vec<Point2> {
+    const TRIES: usize = 30;
+    let mut centres: Vec<Point2> = Vec::with_capacity(n);
+
+    while centres.len() < n {
+        let mut best = (f32::NEG_INFINITY, Vec2::ZERO);
+        for _ in 0..TRIES {
+            let candidate = vec2(
+                rng.gen_range(window_rect.x.start..window_rect.x.end),
+                rng.gen_range(window_rect.y.start..window_rect.y.end),
+            );
+            let nearest = centres
+                .iter()
+                .map(|c| c.distance(candidate))
+                .fold(f32::INFINITY, f32::min);
+            if nearest > best.0 {
+                best = (nearest, candidate);
+            }
+            if nearest >= min_distance {
+                break;
+            }
+        }
+        centres.push(best.1);
+    }
+
+    centres
+}
+
+fn jittered_grid<R: Rng>(window_rect: Rect, n: usize, jitter: f32, rng: &mut R) -> Vec<Point2> {
+    if n == 0 {
+        return vec![];
+    }
+    let rows = ((n as f32 * window_rect.h() / window_rect.w())
+        .sqrt()
+        .round() as usize)
+        .clamp(1, n);
+    let row_height = window_rect.h() / rows as f32;
+
+    // rows share the centres out as evenly as they can, each spreading its own across the width,
+    // so when they don't divide evenly no row is left mostly empty
+    (0..rows)
+        .flat_map(|row| {
+            let columns = n * (row + 1) / rows - n * row / rows;
+            (0..columns).map(move |column| (row, column, columns))
+        })
+        .map(|(row, column, columns)| {
+            let cell = vec2(window_rect.w() / columns as f32, row_height);
+            let middle = vec2(
+                window_rect.left() + cell.x * (column as f32 + 0.5),
+                window_rect.top() - cell.y * (row as f32 + 0.5),
+            );
+            let offset = vec2(rng.gen_range(-0.5..=0.5), rng.gen_range(-0.5..=0.5));
+            middle + offset * cell * jitter
+        })
+        .collect()
+}
+
+/// Rejection sampling on brightness, falling back to the brightest of the tries for images too
+/// dark to ever accept one.
+fn density<R: Rng>(window_rect: Rect, n: usize, image: &GrayImage, rng: &mut R) -> Vec<Point2> {
+    const TRIES: usize = 1000;
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return vec![window_rect.xy(); n];
+    }
+
+    (0..n)
+        .map(|_| {
+            let mut best = (0, window_rect.xy());
+            for _ in 0..TRIES {
+                let (u, v): (f32, f32) = (rng.gen(), rng.gen());
+                let brightness = image.get_pixel(
+                    ((u * width as f32) as u32).min(width - 1),
+                    ((v * height as f32) as u32).min(height - 1),
+                )[0];
+                let point = vec2(
+                    window_rect.left() + u * window_rect.w(),
+                    window_rect.top() - v * window_rect.h(),
+                );
+                if brightness >= best.0 {
+                    best = (brightness, point);
+                }
+                if rng.gen_range(0..255) < brightness {
+                    return point;
+                }
+            }
+            best.1
+        })
+        .collect()
+}
+
+/// Centres from files, for the [`PlacementMode::File`] and [`PlacementMode::Image`] modes.
+#[derive(Clone, Debug, Default)]
+pub struct PlacementFiles {
+    pub points: Option<Arc<Vec<Point2>>>,
+    pub image: Option<Arc<GrayImage>>,
+}
+
+impl PlacementFiles {
+    /// `centres.csv` or `centres.json`, and `centres.png` or `centres.jpg`, in the project folder,
+    /// reporting any that fail to load.
+    pub fn load_from_app(app: &App) -> Self {
+        let dir = match app.project_path() {
+            Ok(path) => path,
+            Err(_) => return PlacementFiles::default(),
+        };
+
+        let mut files = PlacementFiles::default();
+        for path in ["csv", "json", "png", "jpg"].map(|ext| dir.join("centres").with_extension(ext))
+        {
+            if path.exists() {
+                if let Err(e) = files.load(&path) {
+                    eprintln!("failed to load centres {}: {e}", path.display());
+                }
+            }
+        }
+        files
+    }
+
+    /// Load points from a `.csv` or `.json` file, or an image from anything else.
+    pub fn load(&mut self, path: &Path) -> io::Result<PlacementMode> {
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => {
+                self.points = Some(Arc::new(parse_csv(&fs::read_to_string(path)?)?));
+                Ok(PlacementMode::File)
+            }
+            Some("json") => {
+                self.points = Some(Arc::new(parse_json(&fs::read_to_string(path)?)?));
+                Ok(PlacementMode::File)
+            }
+            _ => {
+                let image = nannou::image::open(path).map_err(io::Error::other)?;
+                self.image = Some(Arc::new(image.to_luma8()));
+                Ok(PlacementMode::Image)
+            }
+        }
+    }
+
+    /// Whether `mode` can be used, the file modes need their file loaded.
+    pub fn has(&self, mode: PlacementMode) -> bool {
+        match mode {
+            PlacementMode::File => self.points.is_some(),
+            PlacementMode::Image => self.image.is_some(),
+            _ => true,
+        }
+    }
+
+    /// The placement for `mode`, with its settings from `config`. File modes without a file fall
+    /// back to uniform.
+    pub fn placement(&self, mode: PlacementMode, config: &Config) -> Placement {
+        let radius = config.get_with_default("placement_radius", 0.8);
+        match (mode, &self.points, &self.image) {
+            (PlacementMode::PoissonDisk, _, _) => Placement::PoissonDisk {
+                min_distance: config.get_with_default("min_distance", 120.0),
+            },
+            (PlacementMode::JitteredGrid, _, _) => Placement::JitteredGrid {
+                jitter: config.get_with_default("grid_jitter", 0.5).clamp(0.0, 1.0),
+            },
+            (PlacementMode::Ring, _, _) => Placement::Ring { radius },
+            (PlacementMode::Spiral, _, _) => Placement::Spiral { radius },
+            (PlacementMode::File, Some(points), _) => Placement::Points(points.clone()),
+            (PlacementMode::Image, _, Some(image)) => Placement::Density(image.clone()),
+            _ => Placement::Uniform { padding: 50.0 },
+        }
+    }
+}
+
+/// One `x, y` pair per line, skipping a header and `//` comments.
+pub fn parse_csv(contents: &str) -> io::Result<Vec<Point2>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut points = vec![];
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let parsed = line
+            .split_once(',')
+            .and_then(|(x, y)| Some(vec2(x.trim().parse().ok()?, y.trim().parse().ok()?)));
+        match parsed {
+            Some(point) => points.push(point),
+            None if points.is_empty() && i == 0 => {} // header
+            None => return Err(invalid(format!("line {}: expected `x, y`", i + 1))),
+        }
+    }
+
+    Ok(points)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonPoint {
+    Pair([f32; 2]),
+    Object { x: f32, y: f32 },
+}
+
+/// A list of `[x, y]` pairs or `{"x": .., "y": ..}` objects.
+pub fn parse_json(contents: &str) -> io::Result<Vec<Point2>> {
+    let points: Vec<JsonPoint> = serde_json::from_str(contents)?;
+    Ok(points
+        .into_iter()
+        .map(|p| match p {
+            JsonPoint::Pair([x, y]) | JsonPoint::Object { x, y } => vec2(x, y),
+        })
+        .collect())
+}
@@ -0,0 +1,218 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use nannou::{
+    geom::Rect,
+    image::{self, ImageBuffer, Rgba},
+};
+
+use crate::capture::{is_sink_path, sink_for_path, stream_sink_for_path, FrameSink, StreamFormat};
+use crate::cli::Args;
+use crate::metadata::{save_png, CaptureInfo};
+use crate::output::FrameNames;
+use crate::raster::render_growths_in_bands;
+
+use super::clock::Clock;
+use super::simulation::SimulationSettings;
+use super::snapshot::Snapshot;
+use super::WINDOW_SIZE;
+
+/// Width of renders started from the sketch itself.
+pub const RENDER_SIZE: u32 = 8000;
+/// Rows rendered at once, which bounds the memory a render needs besides the image itself.
+const BAND_HEIGHT: u32 = 512;
+
+/// Render `snapshot` at `width` pixels wide, keeping the aspect ratio of the area it was simulated
+/// in unless a `height` is given. Positions scale with each axis of the image, stroke weights with
+/// the one it shrinks more along.
+///
+/// `.tif`/`.tiff` paths are written as 16-bit TIFF, anything else as an 8-bit image in the format
+/// of its extension.
+pub fn render_snapshot(
+    snapshot: &Snapshot,
+    width: u32,
+    height: Option<u32>,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let world = snapshot.world();
+    let height = height.unwrap_or_else(|| (width as f32 * world.h() / world.w()).round() as u32);
+    let smoothing = snapshot.config.smoothing(snapshot.smoothing_mode);
+    let sixteen_bit = path
+        .extension()
+        .is_some_and(|ext| ext == "tif" || ext == "tiff");
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let render = |write_band: &mut dyn FnMut(u32, crate::raster::Raster)| {
+        render_growths_in_bands(
+            &snapshot.growths,
+            width,
+            height,
+            world,
+            snapshot.background,
+            snapshot.vary_amount,
+            &smoothing,
+            BAND_HEIGHT,
+            write_band,
+        )
+    };
+
+    if sixteen_bit {
+        let mut output: ImageBuffer<Rgba<u16>, Vec<u16>> = ImageBuffer::new(width, height);
+        render(&mut |row, band| image::imageops::replace(&mut output, &band.to_rgba16(), 0, row));
+        output.save(path)?;
+    } else {
+        let mut output = image::RgbaImage::new(width, height);
+        render(&mut |row, band| image::imageops::replace(&mut output, &band.to_rgba8(), 0, row));
+        if path.extension().is_some_and(|ext| ext == "png") {
+            save_png(
+                &output,
+                path,
+                &snapshot_info(snapshot, width, height).text_chunks(),
+            )?;
+        } else {
+            output.save(path)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn snapshot_info(snapshot: &Snapshot, width: u32, height: u32) -> CaptureInfo {
+    let mut info = CaptureInfo::new("mycelium", snapshot.seed, &snapshot.config, (width, height));
+    info.palette = snapshot.palette.clone();
+    info.smoothing = snapshot.smoothing_mode.name().to_string();
+    info
+}
+
+/// `render <snapshot.json> [--width 8000] [--height N] [--out render.png]`
+pub fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let snapshot_path = args
+        .positional(0)
+        .ok_or("usage: render <snapshot.json> [--width 8000] [--height N] [--out render.png]")?;
+    let snapshot = Snapshot::load(Path::new(snapshot_path))?;
+
+    let width = args.get_or("width", RENDER_SIZE)?;
+    let height = args.get("height")?;
+    let out = args.get_or(
+        "out",
+        Path::new(snapshot_path)
+            .with_extension("png")
+            .to_string_lossy()
+            .to_string(),
+    )?;
+
+    render_snapshot(&snapshot, width, height, Path::new(&out))?;
+    println!("rendered {out}");
+    Ok(())
+}
+
+/// Where `animate` puts its frames.
+enum Frames {
+    Files(FrameNames),
+    Sink(Box<dyn FrameSink>),
+}
+
+const ANIMATE_USAGE: &str = "usage: animate [--seed N] [--frames 600] [--fps 30] [--width 900] \
+[--height N] [--preset preset.txt] [--timeline timeline.txt] [--palette name] \
+[--smoothing none|chaikin|catmull-rom] [--out folder] [--stream-format rgba|y4m] \
+[--output-root output] [--output-template {sketch}/{date}-{seed}/{frame}.png]";
+
+/// `animate`: step a new simulation by exactly one frame's worth of time per frame, in the same
+/// whole ticks as the sketch, and write each one to a PNG, so the same seed and preset always make
+/// the same sequence at any `--fps`.
+///
+/// Frames are named by `--output-root` and `--output-template` (see [`FrameNames`]), or go in
+/// `<out>/00001.png` onwards for an `--out` folder. An `--out` that [`sink_for_path`] understands,
+/// like `-` or `out.y4m`, or a `--stream-format`, writes every frame to that instead.
+pub fn run_animation(args: &Args) -> Result<(), Box<dyn Error>> {
+    if args.flag("help") {
+        println!("{ANIMATE_USAGE}");
+        return Ok(());
+    }
+
+    let seed: u64 = args.get_or("seed", rand::random())?;
+    let num_frames: u32 = args.get_or("frames", 600)?;
+    let fps: u32 = args.get_or("fps", 30)?;
+    if fps == 0 {
+        return Err(format!("`--fps` must be above 0\n{ANIMATE_USAGE}").into());
+    }
+    let width = args.get_or("width", WINDOW_SIZE as u32)?;
+    let height = args.get_or("height", width)?;
+    let out = args.get::<PathBuf>("out")?;
+
+    let settings = SimulationSettings::from_args(args)?;
+    let mut simulation = settings.simulation(seed, world_for(width, height));
+    let mut info = settings.capture_info(seed, (width, height));
+    info.fps = Some(fps);
+
+    let stream_format = args.get::<StreamFormat>("stream-format")?;
+    let (mut frames, destination) = match out {
+        Some(out) if stream_format.is_some() || is_sink_path(&out) => {
+            let mut sink = match stream_format {
+                Some(format) => stream_sink_for_path(&out, format, fps)?,
+                None => sink_for_path(&out, fps)?,
+            };
+            sink.set_text_chunks(info.text_chunks());
+            if out != Path::new("-") {
+                info.write_manifest(&out.with_extension("json"))?;
+            }
+            (Frames::Sink(sink), out)
+        }
+        out => {
+            let names = match out {
+                Some(out) => FrameNames::new(&out, "{frame}.png")?,
+                None => {
+                    let mut names = FrameNames::from_args(args, Path::new("output"), "mycelium")?;
+                    names.set("seed", &seed.to_string());
+                    names
+                }
+            };
+            let dir = names.capture_dir();
+            fs::create_dir_all(&dir)?;
+            info.write_manifest(&dir.join("manifest.json"))?;
+            (Frames::Files(names), dir)
+        }
+    };
+
+    let mut clock = Clock::at_fps(fps);
+    for frame in 1..=num_frames {
+        simulation.step_ticks(clock.tick(Duration::ZERO).unwrap_or_default());
+        let image = simulation.render(width, height).to_rgba8();
+        match &mut frames {
+            Frames::Sink(sink) => sink.write_frame(&image)?,
+            Frames::Files(names) => {
+                let mut text_chunks = info.text_chunks();
+                text_chunks.push(("Frame".to_string(), frame.to_string()));
+                let path = names.frame_path(frame as u64);
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                save_png(&image, &path, &text_chunks)?;
+            }
+        }
+        eprint!("\rframe {frame}/{num_frames}");
+    }
+    eprintln!();
+    if let Frames::Sink(sink) = frames {
+        sink.finish()?;
+    }
+
+    // stdout might be carrying the frames
+    eprintln!(
+        "rendered {num_frames} frames of seed {seed} to {}",
+        destination.display()
+    );
+    Ok(())
+}
+
+/// The same shape as the sketch's window, scaled to an image of `width` by `height`.
+pub fn world_for(width: u32, height: u32) -> Rect {
+    Rect::from_w_h(WINDOW_SIZE, WINDOW_SIZE * height as f32 / width as f32)
+}
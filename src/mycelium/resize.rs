@@ -0,0 +1,75 @@
+//! Keeping growths filling the main window when it changes size, e.g. going fullscreen on a
+//! projector.
+
+use std::str::FromStr;
+
+use nannou::prelude::*;
+
+use super::growth::{Growth, Point};
+
+/// What happens to the growths when the main window is resized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResizeMode {
+    /// Stretch everything grown so far to the new size.
+    #[default]
+    Rescale,
+    /// Start again with new growths placed for the new size.
+    Regrow,
+}
+
+impl ResizeMode {
+    pub fn next_mode(self) -> Self {
+        match self {
+            ResizeMode::Rescale => ResizeMode::Regrow,
+            ResizeMode::Regrow => ResizeMode::Rescale,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResizeMode::Rescale => "rescale",
+            ResizeMode::Regrow => "regrow",
+        }
+    }
+}
+
+impl FromStr for ResizeMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [ResizeMode::Rescale, ResizeMode::Regrow]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .ok_or_else(|| format!("unknown resize mode `{name}`"))
+    }
+}
+
+/// `point` moved from somewhere in `from` to the same place relative to `to`.
+pub fn rescale_point(point: Point2, from: Rect, to: Rect) -> Point2 {
+    to.xy() + (point - from.xy()) * (to.wh() / from.wh())
+}
+
+/// Move every growth from `from` to the same place relative to `to`, stretching lines with them.
+pub fn rescale_growths(growths: &mut [Growth], from: Rect, to: Rect) {
+    if from == to || from.w() <= 0.0 || from.h() <= 0.0 {
+        return;
+    }
+    let scale = to.wh() / from.wh();
+    // lines measure progress as a distance, which only keeps its meaning for an even stretch
+    let distance_scale = (scale.x * scale.y).sqrt();
+
+    for growth in growths {
+        growth.centre = rescale_point(growth.centre, from, to);
+        for line in &mut growth.lines {
+            line.start = rescale_point(line.start, from, to);
+            line.end = rescale_point(line.end, from, to);
+            for point in &mut line.points {
+                *point = Point::with_variation(
+                    rescale_point(Point2::from(*point), from, to),
+                    point.variation(),
+                );
+            }
+            line.closest *= distance_scale;
+        }
+    }
+}
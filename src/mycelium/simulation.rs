@@ -0,0 +1,361 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use nannou::prelude::*;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use crate::animation::Curve;
+use crate::cli::Args;
+use crate::metadata::CaptureInfo;
+use crate::palette::{Palette, Palettes};
+use crate::raster::{render_growths, Raster};
+use crate::smoothing::SmoothingMode;
+
+use super::clock::TICK;
+use super::growth::Growth;
+use super::placement::{PlacementFiles, PlacementMode};
+use super::resize::{rescale_growths, ResizeMode};
+use super::snapshot::Snapshot;
+use super::species::{competing, Species, Territory};
+use super::timeline::Timeline;
+use super::topology::TopologyMode;
+use super::{
+    create_new_colonies, mostly_finished, step_growths, vary_amount_at, Config, NUM_GROWTHS,
+};
+
+/// Everything the mycelium sketch grows, without a window. The sketch's `update` steps one of these
+/// too, so a seed and config always play out the same however fast the machine is.
+pub struct Simulation {
+    pub growths: Vec<Growth>,
+    pub config: Config,
+    pub timeline: Timeline,
+    pub palette: Palette,
+    pub species: Vec<Species>,
+    pub smoothing_mode: SmoothingMode,
+    /// Which growths link to which, changes take effect when new growths are made.
+    pub topology_mode: TopologyMode,
+    /// Where growths are placed, changes take effect when new growths are made.
+    pub placement_mode: PlacementMode,
+    pub placement_files: PlacementFiles,
+    pub vary_curve: Curve,
+    pub vary_amount: f32,
+    /// The area growths are placed in, like the sketch's main window.
+    pub world: Rect,
+    pub seed: u64,
+    /// Whether to start again with new growths once most have finished, like the sketch does.
+    pub regenerate: bool,
+    elapsed: Duration,
+    territory: Territory,
+    rng: ChaCha8Rng,
+}
+
+impl Simulation {
+    /// A simulation of `seed` without any growths yet, so the modes can be set with the `with_`
+    /// methods before [`Simulation::start`] grows the first ones.
+    pub fn new(
+        seed: u64,
+        world: Rect,
+        config: Config,
+        palette: Palette,
+        timeline: Timeline,
+    ) -> Self {
+        Simulation {
+            growths: vec![],
+            config,
+            timeline,
+            palette,
+            species: vec![Species::default()],
+            smoothing_mode: SmoothingMode::default(),
+            topology_mode: TopologyMode::default(),
+            placement_mode: PlacementMode::default(),
+            placement_files: PlacementFiles::default(),
+            vary_curve: Curve::Triangle,
+            vary_amount: 1.0,
+            world,
+            seed,
+            regenerate: true,
+            elapsed: Duration::ZERO,
+            territory: Territory::default(),
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// The simulation with growths of `species` instead.
+    pub fn with_species(mut self, species: Vec<Species>) -> Self {
+        self.species = species;
+        self
+    }
+
+    /// The simulation with growths linked by `mode` instead.
+    pub fn with_topology(mut self, mode: TopologyMode) -> Self {
+        self.topology_mode = mode;
+        self
+    }
+
+    /// The simulation with growths placed by `mode` instead, from `files` for the file modes.
+    pub fn with_placement(mut self, mode: PlacementMode, files: PlacementFiles) -> Self {
+        self.placement_mode = mode;
+        self.placement_files = files;
+        self
+    }
+
+    /// The simulation with its first growths, placed and linked by the current modes.
+    pub fn start(mut self) -> Self {
+        self.regrow();
+        self
+    }
+
+    /// Replace every growth with new ones, placed and linked by the current modes.
+    pub fn regrow(&mut self) {
+        self.growths = create_new_colonies(
+            self.world,
+            NUM_GROWTHS,
+            &self.palette,
+            &self.species,
+            &self
+                .placement_files
+                .placement(self.placement_mode, &self.config),
+            &self.config.topology(self.topology_mode),
+            &mut self.rng,
+        );
+        self.territory = Territory::default();
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Who got where first, while species compete.
+    pub fn territory(&self) -> &Territory {
+        &self.territory
+    }
+
+    /// Drives everything random outside of the lines themselves, e.g. where growths are placed.
+    pub fn rng(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
+    /// Switch to `palette`, recolouring every growth whose species doesn't have a colour of its
+    /// own. Colours are picked with `rng` so changing palettes doesn't change what grows.
+    ///
+    /// Growths restored from a snapshot can be of species that aren't loaded, which count as
+    /// having no colour.
+    pub fn set_palette<R: Rng>(&mut self, palette: Palette, rng: &mut R) {
+        for growth in &mut self.growths {
+            let species = self.species.get(growth.species);
+            if species.and_then(|s| s.colour).is_none() {
+                growth.set_colour(palette.choose(rng));
+            }
+        }
+        self.palette = palette;
+    }
+
+    /// Fit the simulation to a new `world`, by `mode`.
+    pub fn resize(&mut self, world: Rect, mode: ResizeMode) {
+        let from = std::mem::replace(&mut self.world, world);
+        match mode {
+            ResizeMode::Rescale => {
+                rescale_growths(&mut self.growths, from, world);
+                self.territory = Territory::from_growths(&self.growths, &self.config);
+            }
+            ResizeMode::Regrow => self.regrow(),
+        }
+    }
+
+    /// Everything needed to carry on from here later.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            seed: self.seed,
+            elapsed: self.elapsed,
+            timeline_playhead: self.timeline.playhead(),
+            config: self.config.clone(),
+            rng: self.rng.clone(),
+            growths: self.growths.clone(),
+            world_size: (self.world.w(), self.world.h()),
+            vary_amount: self.vary_amount,
+            vary_curve: self.vary_curve.clone(),
+            smoothing_mode: self.smoothing_mode,
+            palette: self.palette.name.clone(),
+            background: self.palette.background,
+        }
+    }
+
+    /// Carry on from `snapshot`, rescaled from the size it was taken at to the current `world`. The
+    /// palette is left to the caller, which knows which are loaded.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        let world = std::mem::replace(&mut self.world, snapshot.world());
+        self.seed = snapshot.seed;
+        self.elapsed = snapshot.elapsed;
+        self.timeline.seek_to(snapshot.timeline_playhead);
+        self.config = snapshot.config;
+        self.rng = snapshot.rng;
+        self.growths = snapshot.growths;
+        self.vary_amount = snapshot.vary_amount;
+        self.vary_curve = snapshot.vary_curve;
+        self.smoothing_mode = snapshot.smoothing_mode;
+        self.resize(world, ResizeMode::Rescale);
+    }
+
+    /// Whether most growths have finished, which is when the sketch starts again.
+    pub fn is_finished(&self) -> bool {
+        mostly_finished(&self.growths)
+    }
+
+    pub fn step(&mut self, dt: Duration) {
+        self.elapsed += dt;
+
+        self.timeline.advance(dt);
+        self.timeline.apply(&mut self.config);
+
+        step_growths(&mut self.growths, dt, &self.config);
+        if competing(&self.config) {
+            self.territory.claim(&mut self.growths, &self.config);
+        }
+
+        if self.regenerate && self.is_finished() {
+            self.regrow();
+        }
+
+        self.vary_amount = vary_amount_at(&self.vary_curve, self.elapsed, &self.config);
+    }
+
+    /// Step by `ticks` whole [`TICK`]s, as many as a [`Clock`](super::clock::Clock) says an update is worth.
+    pub fn step_ticks(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.step(TICK);
+        }
+    }
+
+    /// Draw the growths the way the sketch's main window does, with world coordinates.
+    pub fn draw(&self, draw: &Draw) {
+        let smoothing = self.config.smoothing(self.smoothing_mode);
+        for growth in &self.growths {
+            growth.draw(draw, self.vary_amount, &smoothing)
+        }
+    }
+
+    /// The current state at `width` by `height` pixels.
+    pub fn render(&self, width: u32, height: u32) -> Raster {
+        render_growths(
+            &self.growths,
+            width,
+            height,
+            self.world,
+            self.palette.background,
+            self.vary_amount,
+            &self.config.smoothing(self.smoothing_mode),
+        )
+    }
+}
+
+/// The settings of a simulation without a window, from `--preset preset.txt`,
+/// `--timeline timeline.txt`, `--palette name` (from `./palettes`), `--species species.txt`,
+/// `--topology complete|nearest|delaunay|gabriel|spanning-tree|random`,
+/// `--placement uniform|poisson-disk|jittered-grid|ring|spiral|file|image`, `--centres <csv, json or
+/// image>` (picking the file or image placement unless `--placement` says otherwise) and
+/// `--smoothing none|chaikin|catmull-rom`.
+#[derive(Clone, Debug)]
+pub struct SimulationSettings {
+    pub config: Config,
+    pub timeline: Timeline,
+    pub timeline_path: Option<PathBuf>,
+    pub palette: Palette,
+    pub species: Vec<Species>,
+    pub species_path: Option<PathBuf>,
+    pub topology_mode: TopologyMode,
+    pub placement_mode: PlacementMode,
+    pub placement_files: PlacementFiles,
+    pub smoothing_mode: SmoothingMode,
+}
+
+impl SimulationSettings {
+    pub fn from_args(args: &Args) -> Result<Self, Box<dyn Error>> {
+        let config = match args.get::<PathBuf>("preset")? {
+            Some(path) => Config::load_preset(&path)
+                .map_err(|e| format!("failed to load preset {}: {e}", path.display()))?,
+            None => Config::default(),
+        };
+        let timeline_path = args.get::<PathBuf>("timeline")?;
+        let timeline = match &timeline_path {
+            Some(path) => Timeline::load(path)
+                .map_err(|e| format!("failed to load timeline {}: {e}", path.display()))?,
+            None => Timeline::default(),
+        };
+        let species_path = args.get::<PathBuf>("species")?;
+        let species = match &species_path {
+            Some(path) => Species::load_all(path)
+                .map_err(|e| format!("failed to load species {}: {e}", path.display()))?,
+            None => vec![Species::default()],
+        };
+        let mut placement_files = PlacementFiles::default();
+        let mut placement_mode = PlacementMode::default();
+        if let Some(path) = args.get::<PathBuf>("centres")? {
+            placement_mode = placement_files
+                .load(&path)
+                .map_err(|e| format!("failed to load centres {}: {e}", path.display()))?;
+        }
+        if let Some(mode) = args.get::<PlacementMode>("placement")? {
+            if !placement_files.has(mode) {
+                return Err(format!("placement `{}` needs `--centres`", mode.name()).into());
+            }
+            placement_mode = mode;
+        }
+        let mut palettes = Palettes::load_from_dir(Path::new("palettes"));
+        if let Some(name) = args.get::<String>("palette")? {
+            if !palettes.select(&name) {
+                return Err(format!("palette `{name}` isn't in ./palettes").into());
+            }
+        }
+
+        Ok(SimulationSettings {
+            config,
+            timeline,
+            timeline_path,
+            palette: palettes.current().clone(),
+            species,
+            species_path,
+            topology_mode: args.get("topology")?.unwrap_or_default(),
+            placement_mode,
+            placement_files,
+            smoothing_mode: args.get("smoothing")?.unwrap_or_default(),
+        })
+    }
+
+    pub fn simulation(&self, seed: u64, world: Rect) -> Simulation {
+        let mut simulation = Simulation::new(
+            seed,
+            world,
+            self.config.clone(),
+            self.palette.clone(),
+            self.timeline.clone(),
+        )
+        .with_species(self.species.clone())
+        .with_topology(self.topology_mode)
+        .with_placement(self.placement_mode, self.placement_files.clone())
+        .start();
+        simulation.smoothing_mode = self.smoothing_mode;
+        simulation
+    }
+
+    /// Info for a capture of `seed` at `size` pixels with these settings.
+    pub fn capture_info(&self, seed: u64, size: (u32, u32)) -> CaptureInfo {
+        let mut info = CaptureInfo::new("mycelium", seed, &self.config, size);
+        info.palette = self.palette.name.clone();
+        info.smoothing = self.smoothing_mode.name().to_string();
+        info.topology = self.topology_mode.name().to_string();
+        info.placement = self.placement_mode.name().to_string();
+        info.timeline = self
+            .timeline_path
+            .as_ref()
+            .map(|path| path.display().to_string());
+        info.species = self
+            .species_path
+            .as_ref()
+            .map(|path| path.display().to_string());
+        info
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use nannou::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::Curve;
+use crate::output::{project_dir, DATE_FORMAT};
+use crate::palette::Palette;
+use crate::smoothing::SmoothingMode;
+
+use super::{growth::Growth, Config};
+
+/// Everything needed to carry on a simulation exactly where it was left, or to render it again.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub seed: u64,
+    /// Simulation time, see [`super::simulation::Simulation::elapsed`].
+    pub elapsed: Duration,
+    pub timeline_playhead: Duration,
+    pub config: Config,
+    pub rng: ChaCha8Rng,
+    pub growths: Vec<Growth>,
+    /// Size of the area the growths were placed in, centred on the origin.
+    pub world_size: (f32, f32),
+    pub vary_amount: f32,
+    pub vary_curve: Curve,
+    pub smoothing_mode: SmoothingMode,
+    pub palette: String,
+    /// Missing from snapshots saved before it was, which get the default palette's.
+    #[serde(default = "default_background")]
+    pub background: Srgb<u8>,
+}
+
+fn default_background() -> Srgb<u8> {
+    Palette::default().background
+}
+
+impl Snapshot {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = io::BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer(file, self).map_err(io::Error::from)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = io::BufReader::new(fs::File::open(path)?);
+        serde_json::from_reader(file).map_err(io::Error::from)
+    }
+
+    pub fn world(&self) -> Rect {
+        Rect::from_w_h(self.world_size.0, self.world_size.1)
+    }
+}
+
+/// The `snapshots` folder of the project, or of the current directory if that can't be found.
+pub fn snapshots_dir(app: &App) -> PathBuf {
+    project_dir(app).join("snapshots")
+}
+
+/// The `renders` folder of the project, or of the current directory if that can't be found.
+pub fn renders_dir(app: &App) -> PathBuf {
+    project_dir(app).join("renders")
+}
+
+/// The `recordings` folder of the project, or of the current directory if that can't be found.
+pub fn recordings_dir(app: &App) -> PathBuf {
+    project_dir(app).join("recordings")
+}
+
+/// The `lineages` folder of the project, or of the current directory if that can't be found.
+pub fn lineages_dir(app: &App) -> PathBuf {
+    project_dir(app).join("lineages")
+}
+
+/// A new file in `dir` named after the current time.
+pub fn new_snapshot_path(dir: &Path) -> PathBuf {
+    let now = chrono::Local::now().format(DATE_FORMAT);
+    dir.join(format!("{now}.json"))
+}
+
+/// The most recently written snapshot in `dir`.
+pub fn latest_snapshot_path(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok())
+        .map(|e| e.path())
+}
@@ -0,0 +1,247 @@
+//! Species of growth sharing the canvas, each with its own genes, so colonies can look and grow
+//! differently side by side.
+//!
+//! Species are plain text, a `[name]` line starting each one followed by `key = value` lines:
+//!
+//! ```text
+//! // fast and straight, claims ground before the others get there
+//! [runner]
+//! colour = #D7ACCC
+//! weight = 1
+//! step_amount = 1.6
+//! rand_factor = 0.6
+//! branching = 0.4
+//! ```
+//!
+//! `step_amount` and `rand_factor` scale the config values of the same name, `branching` is the
+//! share of the other growths each growth sends lines to and `weight` is how common the species is
+//! compared to the rest. Anything left out is 1, and growths take colours from the palette unless
+//! their species has a `colour`.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use nannou::prelude::*;
+use rand::{distributions::WeightedIndex, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::palette::{parse_hex, Palette};
+
+use super::growth::{Growth, LineState};
+use super::Config;
+
+/// How a growth's lines grow, relative to the config.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Genes {
+    /// Scales the config's `step_amount`.
+    pub step_amount: f32,
+    /// Scales the config's `rand_factor`.
+    pub rand_factor: f32,
+    /// Share of the other growths a growth sends lines to, from 0 to 1.
+    pub branching: f32,
+}
+
+impl Genes {
+    pub fn step_amount(&self, config: &Config) -> f32 {
+        config.get_with_default("step_amount", 8.0) * self.step_amount
+    }
+
+    pub fn rand_factor(&self, config: &Config) -> f32 {
+        config.get_with_default("rand_factor", 1.5) * self.rand_factor
+    }
+}
+
+impl Default for Genes {
+    fn default() -> Self {
+        Genes {
+            step_amount: 1.0,
+            rand_factor: 1.0,
+            branching: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    pub name: String,
+    /// Every growth of the species is this colour, rather than one from the palette.
+    pub colour: Option<Srgb<u8>>,
+    /// How common the species is compared to the others.
+    pub weight: f32,
+    pub genes: Genes,
+}
+
+impl Default for Species {
+    fn default() -> Self {
+        Species {
+            name: "mycelium".to_string(),
+            colour: None,
+            weight: 1.0,
+            genes: Genes::default(),
+        }
+    }
+}
+
+impl Species {
+    /// Every species in a species file, in the order they're written.
+    pub fn load_all(path: &Path) -> io::Result<Vec<Species>> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut species: Vec<Species> = vec![];
+
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                species.push(Species {
+                    name: name.trim().to_string(),
+                    ..Species::default()
+                });
+                continue;
+            }
+
+            let current = species
+                .last_mut()
+                .ok_or_else(|| invalid(format!("line {}: expected `[name]`", i + 1)))?;
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("line {}: expected `key = value`", i + 1)))?;
+            let (key, value) = (key.trim(), value.trim());
+            if key == "colour" {
+                current.colour = Some(parse_hex(value)?);
+                continue;
+            }
+
+            let number: f32 = value
+                .parse()
+                .ok()
+                .filter(|v: &f32| *v >= 0.0)
+                .ok_or_else(|| invalid(format!("line {}: bad value `{value}`", i + 1)))?;
+            match key {
+                "weight" => current.weight = number,
+                "step_amount" => current.genes.step_amount = number,
+                "rand_factor" => current.genes.rand_factor = number,
+                "branching" => current.genes.branching = number.min(1.0),
+                _ => return Err(invalid(format!("line {}: unknown key `{key}`", i + 1))),
+            }
+        }
+
+        if species.is_empty() {
+            return Err(invalid("no species, start one with `[name]`".to_string()));
+        }
+        WeightedIndex::new(species.iter().map(|s| s.weight))
+            .map_err(|e| invalid(format!("species weights: {e}")))?;
+        Ok(species)
+    }
+
+    /// `species.txt` in the project folder, or just the one default species if there isn't one.
+    pub fn load_from_app(app: &App) -> Vec<Species> {
+        let path = match app.project_path() {
+            Ok(path) => path.join("species.txt"),
+            Err(_) => return vec![Species::default()],
+        };
+        if !path.exists() {
+            return vec![Species::default()];
+        }
+
+        Species::load_all(&path).unwrap_or_else(|e| {
+            eprintln!("failed to load species {}: {e}", path.display());
+            vec![Species::default()]
+        })
+    }
+
+    /// The species' own colour, or one from `palette`.
+    pub fn colour<R: Rng>(&self, palette: &Palette, rng: &mut R) -> Srgb<u8> {
+        self.colour.unwrap_or_else(|| palette.choose(rng))
+    }
+}
+
+/// Pick one of `species` by weight, returning its index. With a single species there's nothing to
+/// pick so `rng` isn't touched, keeping seeds from before species growing the same way.
+pub fn choose_species<R: Rng>(species: &[Species], rng: &mut R) -> usize {
+    if species.len() <= 1 {
+        return 0;
+    }
+    WeightedIndex::new(species.iter().map(|s| s.weight))
+        .map(|weights| weights.sample(rng))
+        .unwrap_or(0)
+}
+
+/// Whether species compete for space, set by the config's `competition`.
+pub fn competing(config: &Config) -> bool {
+    config.get_with_default("competition", 0.0) > 0.0
+}
+
+/// Which species got to each patch of the canvas first, for when species compete for space. Patches
+/// are `territory_size` pixels square.
+#[derive(Clone, Debug, Default)]
+pub struct Territory {
+    owners: HashMap<(i32, i32), usize>,
+}
+
+impl Territory {
+    /// The territory as it would be if every point of `growths` had been claimed in turn, e.g. for
+    /// growths restored from a snapshot.
+    pub fn from_growths(growths: &[Growth], config: &Config) -> Self {
+        let size = patch_size(config);
+        let mut territory = Territory::default();
+        for growth in growths {
+            for point in growth.lines.iter().flat_map(|line| &line.points) {
+                territory
+                    .owners
+                    .entry(patch(Point2::from(*point), size))
+                    .or_insert(growth.species);
+            }
+        }
+        territory
+    }
+
+    /// Claim the patch under the tip of every growing line for its species. Lines reaching a patch
+    /// another species got to first go dormant, so faster species take over more of the canvas.
+    pub fn claim(&mut self, growths: &mut [Growth], config: &Config) {
+        let size = patch_size(config);
+        for growth in growths {
+            let species = growth.species;
+            for line in growth.lines.iter_mut().filter(|l| l.state.is_growing()) {
+                let Some(tip) = line.points.last() else {
+                    continue;
+                };
+                match self.owners.entry(patch(Point2::from(*tip), size)) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(species);
+                    }
+                    Entry::Occupied(entry) if *entry.get() != species => {
+                        line.finish(LineState::Dormant, config);
+                    }
+                    Entry::Occupied(_) => {}
+                }
+            }
+        }
+    }
+
+    /// The species that got to `point` first, if any has.
+    pub fn owner(&self, point: Point2, config: &Config) -> Option<usize> {
+        self.owners.get(&patch(point, patch_size(config))).copied()
+    }
+
+    /// How many patches `species` holds.
+    pub fn claimed(&self, species: usize) -> usize {
+        self.owners.values().filter(|s| **s == species).count()
+    }
+}
+
+fn patch_size(config: &Config) -> f32 {
+    config.get_with_default("territory_size", 12.0).max(1.0)
+}
+
+fn patch(point: Point2, size: f32) -> (i32, i32) {
+    (
+        (point.x / size).floor() as i32,
+        (point.y / size).floor() as i32,
+    )
+}
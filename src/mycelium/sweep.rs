@@ -0,0 +1,253 @@
+use std::{error::Error, fs, path::PathBuf, time::Duration};
+
+use rayon::prelude::*;
+
+use crate::cli::Args;
+use crate::metadata::save_png;
+use crate::output::DATE_FORMAT;
+use crate::sheet::{contact_sheet, grid_sheet};
+
+use super::clock::TICK;
+use super::render::world_for;
+use super::simulation::{Simulation, SimulationSettings};
+use super::timeline::Timeline;
+use super::Config;
+
+/// How long to wait for a simulation to finish before rendering it anyway.
+const MAX_SWEEP_SECONDS: f32 = 300.0;
+
+const SWEEP_USAGE: &str = "usage: sweep [--seeds 0..16 | --seeds 3,17,42] [--seconds N] \
+[--thumb 200] [--columns N] [--pick 2,5] [--full 2000] [--out output/sweeps/<date>] \
+[--preset preset.txt] [--timeline timeline.txt] [--palette name] [--smoothing mode]";
+
+/// `sweep`: run a simulation per seed until most of its growths finish, or for `--seconds`, and
+/// tile them into `sheet.png` labelled with their index and seed. `--pick` renders the given
+/// indices again at `--full` pixels.
+pub fn run_seed_sweep(args: &Args) -> Result<(), Box<dyn Error>> {
+    if args.flag("help") {
+        println!("{SWEEP_USAGE}");
+        return Ok(());
+    }
+
+    let seeds = parse_seeds(&args.get_or("seeds", "0..16".to_string())?)?;
+    let seconds: Option<f32> = args.get("seconds")?;
+    let thumb_size = args.get_or("thumb", 200)?;
+    let columns = args.get_or("columns", (seeds.len() as f32).sqrt().ceil() as u32)?;
+    let picks: Vec<usize> = args.get_list("pick")?.unwrap_or_default();
+    if let Some(pick) = picks.iter().find(|i| **i >= seeds.len()) {
+        return Err(format!("can't pick {pick}, there are only {} seeds", seeds.len()).into());
+    }
+    let full_size = args.get_or("full", 2000)?;
+    let out: PathBuf = args.get_or(
+        "out",
+        PathBuf::from("output")
+            .join("sweeps")
+            .join(chrono::Local::now().format(DATE_FORMAT).to_string()),
+    )?;
+    let settings = SimulationSettings::from_args(args)?;
+
+    eprintln!("simulating {} seeds", seeds.len());
+    let simulations: Vec<Simulation> = seeds
+        .par_iter()
+        .map(|seed| run_to_completion(&settings, *seed, seconds))
+        .collect();
+
+    let tiles: Vec<_> = simulations
+        .par_iter()
+        .enumerate()
+        .map(|(i, simulation)| {
+            let thumb = simulation.render(thumb_size, thumb_size).to_rgba8();
+            (thumb, format!("#{i}  seed {}", simulation.seed))
+        })
+        .collect();
+
+    fs::create_dir_all(&out)?;
+    let sheet_path = out.join("sheet.png");
+    let seed_list = seeds.iter().map(u64::to_string).collect::<Vec<_>>();
+    save_png(
+        &contact_sheet(&tiles, columns),
+        &sheet_path,
+        &[("Seeds".to_string(), seed_list.join(","))],
+    )?;
+    println!("contact sheet at {}", sheet_path.display());
+
+    for i in picks {
+        let simulation = &simulations[i];
+        let path = out.join(format!("{i}-{}.png", simulation.seed));
+        let info = settings.capture_info(simulation.seed, (full_size, full_size));
+        save_png(
+            &simulation.render(full_size, full_size).to_rgba8(),
+            &path,
+            &info.text_chunks(),
+        )?;
+        println!("rendered {}", path.display());
+    }
+
+    Ok(())
+}
+
+const GRID_USAGE: &str = "usage: grid --x key=min..max --y key=min..max [--steps 5] \
+[--x-steps N] [--y-steps N] [--seed N] [--seconds N] [--cell 200] [--out output/grids/<date>.png] \
+[--preset preset.txt] [--timeline timeline.txt] [--palette name] [--smoothing mode]";
+
+/// `grid`: render the same seed with two config values varied across a range each, a column per
+/// `--x` value and a row per `--y` value, labelled along the edges.
+pub fn run_grid_sweep(args: &Args) -> Result<(), Box<dyn Error>> {
+    if args.flag("help") {
+        println!("{GRID_USAGE}");
+        return Ok(());
+    }
+
+    let (Some(x_axis), Some(y_axis)) = (args.get::<String>("x")?, args.get::<String>("y")?) else {
+        return Err(GRID_USAGE.into());
+    };
+    let steps = args.get_or("steps", 5)?;
+    let x_axis = Axis::parse(&x_axis, args.get_or("x-steps", steps)?)?;
+    let y_axis = Axis::parse(&y_axis, args.get_or("y-steps", steps)?)?;
+    let seed: u64 = args.get_or("seed", rand::random())?;
+    let seconds: Option<f32> = args.get("seconds")?;
+    let cell_size = args.get_or("cell", 200)?;
+    let out: PathBuf = args.get_or(
+        "out",
+        PathBuf::from("output").join("grids").join(format!(
+            "{}-{seed}.png",
+            chrono::Local::now().format(DATE_FORMAT)
+        )),
+    )?;
+    let settings = SimulationSettings::from_args(args)?;
+    x_axis.check_timeline(&settings.timeline)?;
+    y_axis.check_timeline(&settings.timeline)?;
+
+    let cells: Vec<(f32, f32)> = y_axis
+        .values
+        .iter()
+        .flat_map(|y| x_axis.values.iter().map(move |x| (*x, *y)))
+        .collect();
+    eprintln!("simulating {} cells of seed {seed}", cells.len());
+    let images: Vec<_> = cells
+        .par_iter()
+        .map(|(x, y)| {
+            let mut settings = settings.clone();
+            settings.config.set(&x_axis.key, *x);
+            settings.config.set(&y_axis.key, *y);
+            run_to_completion(&settings, seed, seconds)
+                .render(cell_size, cell_size)
+                .to_rgba8()
+        })
+        .collect();
+
+    let sheet = grid_sheet(
+        &images,
+        &format!("{} \u{2192}", x_axis.key),
+        &x_axis.labels(),
+        &format!("{} \u{2193}", y_axis.key),
+        &y_axis.labels(),
+    );
+    if let Some(dir) = out.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let info = settings.capture_info(seed, sheet.dimensions());
+    save_png(&sheet, &out, &info.text_chunks())?;
+    println!("grid at {}", out.display());
+
+    Ok(())
+}
+
+/// A config key and the values it takes along one side of a grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Axis {
+    pub key: String,
+    pub values: Vec<f32>,
+}
+
+impl Axis {
+    /// `key=min..max` split into `steps` evenly spaced values, both ends included. The key must be
+    /// one of the config's.
+    pub fn parse(axis: &str, steps: usize) -> Result<Self, String> {
+        let bad = || format!("bad axis `{axis}`, use key=min..max like rand_factor=0.5..4");
+        let (key, range) = axis.split_once('=').ok_or_else(bad)?;
+        let key = key.trim();
+        if !Config::default().keys.iter().any(|k| k == key) {
+            return Err(format!("`{key}` isn't a config key"));
+        }
+        let (min, max) = range.split_once("..").ok_or_else(bad)?;
+        let min: f32 = min.trim().parse().map_err(|_| bad())?;
+        let max: f32 = max.trim().parse().map_err(|_| bad())?;
+
+        let values = match steps {
+            0 => return Err(format!("`{axis}` needs at least one step")),
+            1 => vec![min],
+            _ => (0..steps)
+                .map(|i| min + (max - min) * i as f32 / (steps - 1) as f32)
+                .collect(),
+        };
+
+        Ok(Axis {
+            key: key.to_string(),
+            values,
+        })
+    }
+
+    /// An error if `timeline` has a track for the key, which would override the axis values as
+    /// soon as the simulation steps.
+    pub fn check_timeline(&self, timeline: &Timeline) -> Result<(), String> {
+        if timeline.tracks().any(|(key, _)| *key == self.key) {
+            return Err(format!(
+                "the timeline animates `{}`, which would override the sweep",
+                self.key
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn labels(&self) -> Vec<String> {
+        self.values
+            .iter()
+            .map(|value| {
+                let label = format!("{value:.2}");
+                label
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+/// A simulation of `seed` stepped by the sketch's [`TICK`]s until most of its growths finish, or for `seconds`.
+pub fn run_to_completion(
+    settings: &SimulationSettings,
+    seed: u64,
+    seconds: Option<f32>,
+) -> Simulation {
+    let mut simulation = settings.simulation(seed, world_for(1, 1));
+    simulation.regenerate = false;
+
+    let limit = Duration::from_secs_f32(seconds.unwrap_or(MAX_SWEEP_SECONDS));
+    while simulation.elapsed() < limit && (seconds.is_some() || !simulation.is_finished()) {
+        simulation.step(TICK);
+    }
+    simulation
+}
+
+/// `first..last`, `first..=last` or a comma separated list.
+pub fn parse_seeds(seeds: &str) -> Result<Vec<u64>, String> {
+    let bad = |_| format!("bad seeds `{seeds}`, use a range like 0..16 or a list like 3,17,42");
+
+    if let Some((first, last)) = seeds.split_once("..") {
+        let first: u64 = first.trim().parse().map_err(bad)?;
+        let range: Vec<u64> = match last.strip_prefix('=') {
+            Some(last) => (first..=last.trim().parse().map_err(bad)?).collect(),
+            None => (first..last.trim().parse().map_err(bad)?).collect(),
+        };
+        if range.is_empty() {
+            return Err(format!("`{seeds}` has no seeds in it"));
+        }
+        return Ok(range);
+    }
+
+    seeds
+        .split(',')
+        .map(|seed| seed.trim().parse().map_err(bad))
+        .collect()
+}
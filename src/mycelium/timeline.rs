@@ -0,0 +1,224 @@
+//! Keyframes for `Config` values, so a performance can be choreographed ahead of time.
+//!
+//! Timelines are plain text with one keyframe per line:
+//!
+//! ```text
+//! // key        seconds  value  interpolation to the next keyframe
+//! rand_factor   0        1.5    ease-in
+//! rand_factor   60       6.0
+//! ```
+//!
+//! The interpolation is one of `step`, `linear` (the default), `smooth`, `ease-in`, `ease-out` or
+//! `ease-in-out`. Keys have to be ones the default config has, so a typo is an error rather than
+//! a track that never takes effect.
+
+use std::{collections::BTreeMap, fs, io, path::Path, time::Duration};
+
+use nannou::prelude::*;
+
+use crate::animation::{ease_in_cubic, ease_in_out, ease_out_cubic, smoothstep};
+
+use super::Config;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Hold the value until the next keyframe.
+    Step,
+    Linear,
+    Smooth,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Interpolation {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "step" => Some(Interpolation::Step),
+            "linear" => Some(Interpolation::Linear),
+            "smooth" => Some(Interpolation::Smooth),
+            "ease-in" => Some(Interpolation::EaseIn),
+            "ease-out" => Some(Interpolation::EaseOut),
+            "ease-in-out" => Some(Interpolation::EaseInOut),
+            _ => None,
+        }
+    }
+
+    /// How far between two keyframes the value is, `t` of the way between them in time.
+    fn apply(&self, t: f32) -> f32 {
+        match self {
+            Interpolation::Step => 0.0,
+            Interpolation::Linear => t,
+            Interpolation::Smooth => smoothstep(t),
+            Interpolation::EaseIn => ease_in_cubic(t),
+            Interpolation::EaseOut => ease_out_cubic(t),
+            Interpolation::EaseInOut => ease_in_out(t, ease_in_cubic),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    pub time: Duration,
+    pub value: f32,
+    /// How to get from this keyframe to the next one.
+    pub interpolation: Interpolation,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Timeline {
+    tracks: BTreeMap<String, Vec<Keyframe>>,
+    playhead: Duration,
+    playing: bool,
+}
+
+impl Timeline {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut tracks: BTreeMap<String, Vec<Keyframe>> = BTreeMap::new();
+        let keys = Config::default().keys;
+
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (key, time, value, interpolation) = match words[..] {
+                [key, time, value] => (key, time, value, "linear"),
+                [key, time, value, interpolation] => (key, time, value, interpolation),
+                _ => {
+                    return Err(invalid(format!(
+                        "line {}: expected `key seconds value`",
+                        i + 1
+                    )))
+                }
+            };
+            if !keys.iter().any(|k| k == key) {
+                return Err(invalid(format!(
+                    "line {}: `{key}` isn't a config key",
+                    i + 1
+                )));
+            }
+
+            let keyframe = Keyframe {
+                time: time
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|t| *t >= 0.0)
+                    .and_then(|t| Duration::try_from_secs_f32(t).ok())
+                    .ok_or_else(|| invalid(format!("line {}: bad time `{time}`", i + 1)))?,
+                value: value
+                    .parse()
+                    .ok()
+                    .filter(|v: &f32| v.is_finite())
+                    .ok_or_else(|| invalid(format!("line {}: bad value `{value}`", i + 1)))?,
+                interpolation: Interpolation::parse(interpolation).ok_or_else(|| {
+                    invalid(format!(
+                        "line {}: unknown interpolation `{interpolation}`",
+                        i + 1
+                    ))
+                })?,
+            };
+            tracks.entry(key.to_string()).or_default().push(keyframe);
+        }
+
+        for keyframes in tracks.values_mut() {
+            keyframes.sort_by_key(|k| k.time);
+        }
+
+        Ok(Timeline {
+            tracks,
+            playhead: Duration::ZERO,
+            playing: true,
+        })
+    }
+
+    /// `timeline.txt` in the project folder, or an empty timeline if there isn't one.
+    pub fn load_from_app(app: &App) -> Self {
+        let path = match app.project_path() {
+            Ok(path) => path.join("timeline.txt"),
+            Err(_) => return Timeline::default(),
+        };
+        if !path.exists() {
+            return Timeline::default();
+        }
+
+        Timeline::load(&path).unwrap_or_else(|e| {
+            eprintln!("failed to load timeline {}: {e}", path.display());
+            Timeline::default()
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    pub fn tracks(&self) -> impl Iterator<Item = (&String, &Vec<Keyframe>)> {
+        self.tracks.iter()
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> Duration {
+        self.tracks
+            .values()
+            .filter_map(|keyframes| keyframes.last())
+            .map(|k| k.time)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn playhead(&self) -> Duration {
+        self.playhead
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn toggle_playing(&mut self) {
+        self.playing = !self.playing;
+    }
+
+    /// Move the playhead by `seconds`, backwards if negative. Seeking further than a `Duration`
+    /// can hold leaves the playhead where it is.
+    pub fn seek_by(&mut self, seconds: f32) {
+        let playhead = (self.playhead.as_secs_f32() + seconds).max(0.0);
+        if let Ok(playhead) = Duration::try_from_secs_f32(playhead) {
+            self.playhead = playhead;
+        }
+    }
+
+    pub fn seek_to(&mut self, playhead: Duration) {
+        self.playhead = playhead;
+    }
+
+    pub fn advance(&mut self, dt: Duration) {
+        if self.playing {
+            self.playhead += dt;
+        }
+    }
+
+    pub fn value_at(keyframes: &[Keyframe], time: Duration) -> Option<f32> {
+        let after = keyframes.iter().position(|k| k.time > time);
+        match after {
+            None => keyframes.last().map(|k| k.value),
+            Some(0) => keyframes.first().map(|k| k.value),
+            Some(i) => {
+                let (a, b) = (keyframes[i - 1], keyframes[i]);
+                let t = (time - a.time).as_secs_f32() / (b.time - a.time).as_secs_f32();
+                Some(a.value + (b.value - a.value) * a.interpolation.apply(t))
+            }
+        }
+    }
+
+    /// Set every config value with a track to its value at the playhead.
+    pub fn apply(&self, config: &mut Config) {
+        for (key, keyframes) in &self.tracks {
+            if let Some(value) = Timeline::value_at(keyframes, self.playhead) {
+                config.set(key, value);
+            }
+        }
+    }
+}
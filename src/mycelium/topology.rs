@@ -0,0 +1,240 @@
+//! Which growths send lines to which, as a graph over their centres.
+
+use std::{collections::BTreeSet, str::FromStr};
+
+use nannou::prelude::*;
+use rand::prelude::*;
+
+/// The kinds of [`Topology`], which the sketch cycles through. Their settings come from the config,
+/// see [`super::Config::topology`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TopologyMode {
+    #[default]
+    Complete,
+    Nearest,
+    Delaunay,
+    Gabriel,
+    SpanningTree,
+    Random,
+}
+
+impl TopologyMode {
+    pub fn next_mode(self) -> Self {
+        match self {
+            TopologyMode::Complete => TopologyMode::Nearest,
+            TopologyMode::Nearest => TopologyMode::Delaunay,
+            TopologyMode::Delaunay => TopologyMode::Gabriel,
+            TopologyMode::Gabriel => TopologyMode::SpanningTree,
+            TopologyMode::SpanningTree => TopologyMode::Random,
+            TopologyMode::Random => TopologyMode::Complete,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TopologyMode::Complete => "complete",
+            TopologyMode::Nearest => "nearest",
+            TopologyMode::Delaunay => "delaunay",
+            TopologyMode::Gabriel => "gabriel",
+            TopologyMode::SpanningTree => "spanning-tree",
+            TopologyMode::Random => "random",
+        }
+    }
+}
+
+impl FromStr for TopologyMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [
+            TopologyMode::Complete,
+            TopologyMode::Nearest,
+            TopologyMode::Delaunay,
+            TopologyMode::Gabriel,
+            TopologyMode::SpanningTree,
+            TopologyMode::Random,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == name)
+        .ok_or_else(|| format!("unknown topology `{name}`"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Topology {
+    /// Every growth to every other.
+    Complete,
+    /// Every growth to the `k` closest to it.
+    Nearest { k: usize },
+    /// The edges of the Delaunay triangulation of the centres.
+    Delaunay,
+    /// Delaunay edges whose diameter circle has no other centre in it, a sparser web without long
+    /// edges cutting across.
+    Gabriel,
+    /// The minimum spanning tree, the shortest edges that still connect every growth.
+    SpanningTree,
+    /// Each pair linked with probability `p`, an Erdős–Rényi graph.
+    Random { p: f32 },
+}
+
+impl Topology {
+    /// For each of `centres`, the indices of the centres it sends lines to, in order. A centre is
+    /// never linked to itself, and linked both ways for every kind but [`Topology::Nearest`].
+    pub fn links<R: Rng>(&self, centres: &[Point2], rng: &mut R) -> Vec<Vec<usize>> {
+        let n = centres.len();
+        match *self {
+            Topology::Complete => (0..n)
+                .map(|i| (0..n).filter(|j| *j != i).collect())
+                .collect(),
+            Topology::Nearest { k } => (0..n)
+                .map(|i| {
+                    let mut others: Vec<usize> = (0..n).filter(|j| *j != i).collect();
+                    others.sort_by(|a, b| {
+                        let d_a = centres[i].distance_squared(centres[*a]);
+                        let d_b = centres[i].distance_squared(centres[*b]);
+                        d_a.total_cmp(&d_b)
+                    });
+                    others.truncate(k);
+                    others.sort_unstable();
+                    others
+                })
+                .collect(),
+            Topology::Delaunay => adjacency(n, delaunay(centres)),
+            Topology::Gabriel => adjacency(n, gabriel(centres)),
+            Topology::SpanningTree => adjacency(n, spanning_tree(centres)),
+            Topology::Random { p } => {
+                let edges = (0..n)
+                    .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                    .filter(|_| rng.gen::<f32>() < p)
+                    .collect();
+                adjacency(n, edges)
+            }
+        }
+    }
+}
+
+/// Undirected `edges` as a list of neighbours for each of `n` nodes.
+fn adjacency(n: usize, edges: BTreeSet<(usize, usize)>) -> Vec<Vec<usize>> {
+    let mut links = vec![vec![]; n];
+    for (a, b) in edges {
+        links[a].push(b);
+        links[b].push(a);
+    }
+    for neighbours in &mut links {
+        neighbours.sort_unstable();
+    }
+    links
+}
+
+/// Edges of the Delaunay triangulation, by Bowyer–Watson, as `(smaller, larger)` index pairs.
+pub fn delaunay(points: &[Point2]) -> BTreeSet<(usize, usize)> {
+    let n = points.len();
+    if n < 3 {
+        return (n == 2).then_some((0, 1)).into_iter().collect();
+    }
+
+    // doubles, so nearly cocircular points don't flip the circumcircle tests
+    let mut vertices: Vec<DVec2> = points.iter().map(|p| p.as_f64()).collect();
+    let min = vertices.iter().fold(vertices[0], |min, v| min.min(*v));
+    let max = vertices.iter().fold(vertices[0], |max, v| max.max(*v));
+    let size = (max - min).max_element().max(1.0) * 20.0;
+    let mid = (min + max) / 2.0;
+    vertices.extend([
+        mid + dvec2(-size, -size),
+        mid + dvec2(size, -size),
+        mid + dvec2(0.0, size),
+    ]);
+
+    let mut triangles = vec![[n, n + 1, n + 2]];
+    for i in 0..n {
+        let point = vertices[i];
+        let (bad, good): (Vec<[usize; 3]>, Vec<[usize; 3]>) = triangles
+            .into_iter()
+            .partition(|t| in_circumcircle(point, t.map(|v| vertices[v])));
+
+        // the hole left by the bad triangles is bounded by the edges only one of them has
+        let edges: Vec<(usize, usize)> = bad
+            .iter()
+            .flat_map(|[a, b, c]| [(*a, *b), (*b, *c), (*c, *a)])
+            .collect();
+        let boundary = edges.iter().filter(|(a, b)| {
+            edges
+                .iter()
+                .filter(|(c, d)| (a, b) == (c, d) || (a, b) == (d, c))
+                .count()
+                == 1
+        });
+
+        triangles = good;
+        triangles.extend(boundary.map(|(a, b)| [*a, *b, i]));
+    }
+
+    triangles
+        .iter()
+        .filter(|t| t.iter().all(|v| *v < n))
+        .flat_map(|[a, b, c]| [(*a, *b), (*b, *c), (*c, *a)])
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect()
+}
+
+fn in_circumcircle(point: DVec2, [a, b, c]: [DVec2; 3]) -> bool {
+    let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+    if d == 0.0 {
+        return false;
+    }
+    let (a2, b2, c2) = (a.length_squared(), b.length_squared(), c.length_squared());
+    let centre = dvec2(
+        (a2 * (b.y - c.y) + b2 * (c.y - a.y) + c2 * (a.y - b.y)) / d,
+        (a2 * (c.x - b.x) + b2 * (a.x - c.x) + c2 * (b.x - a.x)) / d,
+    );
+    point.distance_squared(centre) < a.distance_squared(centre)
+}
+
+/// Delaunay edges with no other point inside the circle they're the diameter of.
+pub fn gabriel(points: &[Point2]) -> BTreeSet<(usize, usize)> {
+    delaunay(points)
+        .into_iter()
+        .filter(|&(a, b)| {
+            let mid = (points[a] + points[b]) / 2.0;
+            let radius_squared = points[a].distance_squared(points[b]) / 4.0;
+            points
+                .iter()
+                .enumerate()
+                .all(|(i, p)| i == a || i == b || p.distance_squared(mid) >= radius_squared)
+        })
+        .collect()
+}
+
+/// Edges of the minimum spanning tree, by Prim's algorithm over every pair of points.
+pub fn spanning_tree(points: &[Point2]) -> BTreeSet<(usize, usize)> {
+    let n = points.len();
+    let mut edges = BTreeSet::new();
+    if n == 0 {
+        return edges;
+    }
+
+    let mut in_tree = vec![false; n];
+    // the closest tree node to each node outside the tree, and how far away it is
+    let mut closest: Vec<(f32, usize)> = vec![(f32::INFINITY, 0); n];
+    let mut next = 0;
+    for _ in 0..n {
+        in_tree[next] = true;
+        for i in (0..n).filter(|i| !in_tree[*i]) {
+            let d = points[next].distance_squared(points[i]);
+            if d < closest[i].0 {
+                closest[i] = (d, next);
+            }
+        }
+
+        let Some(i) = (0..n)
+            .filter(|i| !in_tree[*i])
+            .min_by(|a, b| closest[*a].0.total_cmp(&closest[*b].0))
+        else {
+            break;
+        };
+        let parent = closest[i].1;
+        edges.insert((parent.min(i), parent.max(i)));
+        next = i;
+    }
+    edges
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use nannou::prelude::*;
+
+use crate::cli::Args;
+
+/// Dates in file names, without the colons some filesystems and sync tools choke on.
+pub const DATE_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+/// Where each frame goes under the output root unless `--output-template` says otherwise.
+pub const DEFAULT_TEMPLATE: &str = "{sketch}/{date}-{seed}/{frame}.png";
+/// Tokens a template can use, each written as `{token}`.
+pub const TOKENS: [&str; 5] = ["sketch", "seed", "date", "frame", "preset"];
+
+/// The project folder, or the current directory if it can't be found, e.g. for an installed binary.
+pub fn project_dir(app: &App) -> PathBuf {
+    app.project_path().unwrap_or_else(|_| PathBuf::from("."))
+}
+
+/// Names the frames of a capture from an output root and a template like [`DEFAULT_TEMPLATE`].
+/// Tokens without a value are left out, so `{date}-{seed}` is just the date for no seed.
+#[derive(Clone, Debug)]
+pub struct FrameNames {
+    root: PathBuf,
+    template: String,
+    values: HashMap<&'static str, String>,
+}
+
+impl FrameNames {
+    pub fn new(root: &Path, template: &str) -> Result<Self, String> {
+        check_template(template)?;
+
+        let mut values = HashMap::new();
+        values.insert("date", chrono::Local::now().format(DATE_FORMAT).to_string());
+
+        Ok(FrameNames {
+            root: root.to_path_buf(),
+            template: template.to_string(),
+            values,
+        })
+    }
+
+    /// `--output-root` and `--output-template` from the command line, defaulting to the `output`
+    /// folder of the project and [`DEFAULT_TEMPLATE`]. `sketch` fills in the `{sketch}` token.
+    pub fn from_args(
+        args: &Args,
+        default_root: &Path,
+        sketch: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let root = args.get_or("output-root", default_root.to_path_buf())?;
+        let template: String = args.get_or("output-template", DEFAULT_TEMPLATE.to_string())?;
+        let mut names = FrameNames::new(&root, &template)?;
+        names.set("sketch", sketch);
+        if let Some(preset) = args.get::<PathBuf>("preset")? {
+            let preset = preset.file_stem().unwrap_or_default().to_string_lossy();
+            names.set("preset", &preset);
+        }
+        Ok(names)
+    }
+
+    /// Fill in `{token}`, which must be one of [`TOKENS`] other than `frame`.
+    pub fn set(&mut self, token: &'static str, value: &str) {
+        debug_assert!(TOKENS.contains(&token) && token != "frame");
+        self.values.insert(token, value.to_string());
+    }
+
+    pub fn frame_path(&self, frame: u64) -> PathBuf {
+        let frame = format!("{frame:05}");
+        let expanded = expand(&self.template, |token| match token {
+            "frame" => Some(frame.as_str()),
+            _ => self.values.get(token).map(String::as_str),
+        });
+
+        // drop folders left empty by tokens without a value
+        expanded
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .fold(self.root.clone(), |path, part| path.join(part))
+    }
+
+    /// The folder the first frame goes in, for anything describing the whole capture.
+    pub fn capture_dir(&self) -> PathBuf {
+        self.frame_path(1)
+            .parent()
+            .map_or_else(|| self.root.clone(), Path::to_path_buf)
+    }
+}
+
+fn check_template(template: &str) -> Result<(), String> {
+    let mut unknown = None;
+    expand(template, |token| {
+        if !TOKENS.contains(&token) {
+            unknown.get_or_insert_with(|| token.to_string());
+        }
+        None
+    });
+
+    match unknown {
+        Some(token) => Err(format!(
+            "unknown token `{{{token}}}` in `{template}`, use any of {}",
+            TOKENS.map(|t| format!("{{{t}}}")).join(" ")
+        )),
+        None if !template.contains("{frame}") => {
+            Err(format!("`{template}` needs a `{{frame}}` token"))
+        }
+        None => Ok(()),
+    }
+}
+
+/// `template` with every `{token}` replaced by its value, anything else is kept as it is. A token
+/// without a value takes the `-` or `_` separators next to it along, from before it in the same
+/// folder name if there are any there, or else from after it.
+fn expand<'a>(template: &str, mut value: impl FnMut(&str) -> Option<&'a str>) -> String {
+    let is_separator = |c: char| c == '-' || c == '_';
+    let mut expanded = String::new();
+    let mut rest = template;
+    // set by a token without a value that had no separators before it to take
+    let mut trim_next = false;
+    let push = |expanded: &mut String, text: &str, trim_next: &mut bool| {
+        let text = if *trim_next {
+            text.trim_start_matches(is_separator)
+        } else {
+            text
+        };
+        if !text.is_empty() {
+            *trim_next = false;
+        }
+        expanded.push_str(text);
+    };
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        push(&mut expanded, &rest[..start], &mut trim_next);
+        match value(&rest[start + 1..start + end]).filter(|v| !v.is_empty()) {
+            Some(value) => push(&mut expanded, value, &mut trim_next),
+            None => {
+                let folder_start = expanded.rfind('/').map_or(0, |i| i + 1);
+                let kept = expanded[folder_start..]
+                    .trim_end_matches(is_separator)
+                    .len();
+                if folder_start + kept < expanded.len() {
+                    expanded.truncate(folder_start + kept);
+                } else {
+                    trim_next = true;
+                }
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    push(&mut expanded, rest, &mut trim_next);
+    expanded
+}
@@ -0,0 +1,318 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use nannou::{color::rgb_u32, prelude::*};
+use rand::{distributions::WeightedIndex, prelude::*};
+
+pub const FRENCH_GREY: u32 = 0xC6BCC8;
+pub const PINK_LAVENDAR: u32 = 0xD7ACCC;
+pub const THISTLE: u32 = 0xD1B3BD;
+pub const CHAMPAGNE: u32 = 0xFCE5C5;
+pub const TEA_GREEN: u32 = 0xC5DCBC;
+pub const COLOURS: [u32; 5] = [FRENCH_GREY, PINK_LAVENDAR, THISTLE, TEA_GREEN, CHAMPAGNE];
+
+/// Name a colour is given in `.gpl` and `.ase` files to be used as the background.
+const BACKGROUND_NAME: &str = "background";
+
+#[derive(Clone, Debug)]
+pub struct Palette {
+    pub name: String,
+    pub background: Srgb<u8>,
+    colours: Vec<Srgb<u8>>,
+    weights: WeightedIndex<f32>,
+}
+
+impl Palette {
+    pub fn new(name: &str, background: Srgb<u8>, entries: &[(Srgb<u8>, f32)]) -> io::Result<Self> {
+        let weights = WeightedIndex::new(entries.iter().map(|(_, w)| *w))
+            .map_err(|e| invalid_data(format!("palette `{name}`: {e}")))?;
+
+        Ok(Palette {
+            name: name.to_string(),
+            background,
+            colours: entries.iter().map(|(c, _)| *c).collect(),
+            weights,
+        })
+    }
+
+    /// Load a palette, picking the format from the file extension: `.gpl` (GIMP), `.ase` (Adobe
+    /// Swatch Exchange) or anything else as a list of hex colours.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+
+        match extension.as_deref() {
+            Some("gpl") => parse_gpl(&name, &fs::read_to_string(path)?),
+            Some("ase") => parse_ase(&name, &fs::read(path)?),
+            _ => parse_hex_list(&name, &fs::read_to_string(path)?),
+        }
+    }
+
+    pub fn colours(&self) -> &[Srgb<u8>] {
+        &self.colours
+    }
+
+    /// Pick a colour at random, favouring the ones with a higher weight.
+    pub fn choose<R: Rng>(&self, rng: &mut R) -> Srgb<u8> {
+        self.colours[self.weights.sample(rng)]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        let entries: Vec<(Srgb<u8>, f32)> = COLOURS.iter().map(|c| (rgb_u32(*c), 1.0)).collect();
+        Palette::new("liturgy", rgb_u32(0x000000), &entries).unwrap()
+    }
+}
+
+/// The palettes the sketch can cycle through, always starting with the default one.
+pub struct Palettes {
+    palettes: Vec<Palette>,
+    selected: usize,
+}
+
+impl Palettes {
+    /// Load every palette file in `dir`, skipping (and reporting) any that fail to parse.
+    pub fn load_from_dir(dir: &Path) -> Self {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default();
+        paths.sort();
+
+        let mut palettes = vec![Palette::default()];
+        for path in paths.iter().filter(|p| p.is_file()) {
+            match Palette::load(path) {
+                Ok(palette) => palettes.push(palette),
+                Err(e) => eprintln!("skipping palette {}: {e}", path.display()),
+            }
+        }
+
+        Palettes {
+            palettes,
+            selected: 0,
+        }
+    }
+
+    /// Palettes from the `palettes` folder of the project, if it can be found.
+    pub fn load_from_app(app: &App) -> Self {
+        match app.project_path() {
+            Ok(path) => Palettes::load_from_dir(&path.join("palettes")),
+            Err(_) => Palettes::default(),
+        }
+    }
+
+    pub fn current(&self) -> &Palette {
+        &self.palettes[self.selected]
+    }
+
+    /// Switch to the palette called `name`, returning whether there is one.
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.palettes.iter().position(|p| p.name == name) {
+            Some(i) => {
+                self.selected = i;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn next_palette(&mut self) -> &Palette {
+        self.selected = (self.selected + 1) % self.palettes.len();
+        self.current()
+    }
+}
+
+impl Default for Palettes {
+    fn default() -> Self {
+        Palettes {
+            palettes: vec![Palette::default()],
+            selected: 0,
+        }
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// A `#RRGGBB` colour.
+pub(crate) fn parse_hex(hex: &str) -> io::Result<Srgb<u8>> {
+    let digits = hex.trim_start_matches('#').trim_start_matches("0x");
+    if digits.len() != 6 {
+        return Err(invalid_data(format!("`{hex}` is not a 6 digit hex colour")));
+    }
+    u32::from_str_radix(digits, 16)
+        .map(rgb_u32)
+        .map_err(|_| invalid_data(format!("`{hex}` is not a hex colour")))
+}
+
+/// One colour per line as `#RRGGBB [weight]`, with an optional `background #RRGGBB` line and `//`
+/// comments.
+fn parse_hex_list(name: &str, contents: &str) -> io::Result<Palette> {
+    let mut background = rgb_u32(0x000000);
+    let mut entries = vec![];
+
+    for line in contents.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (None, _) => {}
+            (Some("background"), Some(hex)) => background = parse_hex(hex)?,
+            (Some(hex), weight) => {
+                let weight = match weight {
+                    Some(w) => w
+                        .parse()
+                        .map_err(|_| invalid_data(format!("`{w}` is not a weight")))?,
+                    None => 1.0,
+                };
+                entries.push((parse_hex(hex)?, weight));
+            }
+        }
+    }
+
+    Palette::new(name, background, &entries)
+}
+
+/// GIMP palette: a `GIMP Palette` header, optional `Name:`/`Columns:` lines, `#` comments and then
+/// `R G B [colour name]` per line.
+fn parse_gpl(name: &str, contents: &str) -> io::Result<Palette> {
+    let mut lines = contents.lines();
+    if lines.next().map(str::trim) != Some("GIMP Palette") {
+        return Err(invalid_data("missing `GIMP Palette` header".to_string()));
+    }
+
+    let mut name = name.to_string();
+    let mut background = rgb_u32(0x000000);
+    let mut entries = vec![];
+
+    for line in lines.map(str::trim) {
+        if let Some(n) = line.strip_prefix("Name:") {
+            name = n.trim().to_string();
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let mut channel = || -> io::Result<u8> {
+            words
+                .next()
+                .and_then(|w| w.parse().ok())
+                .ok_or_else(|| invalid_data(format!("bad colour line `{line}`")))
+        };
+        let colour = Srgb::new(channel()?, channel()?, channel()?);
+        let colour_name = words.collect::<Vec<_>>().join(" ");
+
+        if colour_name.eq_ignore_ascii_case(BACKGROUND_NAME) {
+            background = colour;
+        } else {
+            entries.push((colour, 1.0));
+        }
+    }
+
+    Palette::new(&name, background, &entries)
+}
+
+/// Adobe Swatch Exchange: an `ASEF` header followed by big-endian blocks, of which only colour
+/// entries in the RGB, CMYK and Gray models are used.
+fn parse_ase(name: &str, bytes: &[u8]) -> io::Result<Palette> {
+    const COLOUR_ENTRY: u16 = 0x0001;
+
+    let mut reader = AseReader { bytes, position: 0 };
+    if reader.take(4)? != b"ASEF" {
+        return Err(invalid_data("missing `ASEF` header".to_string()));
+    }
+    // version
+    reader.take(4)?;
+    let num_blocks = reader.u32()?;
+
+    let mut background = rgb_u32(0x000000);
+    let mut entries = vec![];
+
+    for _ in 0..num_blocks {
+        let block_type = reader.u16()?;
+        let block_length = reader.u32()? as usize;
+        let mut block = AseReader {
+            bytes: reader.take(block_length)?,
+            position: 0,
+        };
+        if block_type != COLOUR_ENTRY {
+            continue;
+        }
+
+        let name_length = block.u16()? as usize;
+        let name_units: Vec<u16> = (0..name_length)
+            .map(|_| block.u16())
+            .collect::<io::Result<_>>()?;
+        let colour_name = String::from_utf16_lossy(&name_units);
+        let colour_name = colour_name.trim_end_matches('\0');
+
+        let model = block.take(4)?.to_vec();
+        let (r, g, b) = match &model[..] {
+            b"RGB " => (block.f32()?, block.f32()?, block.f32()?),
+            b"Gray" => {
+                let v = block.f32()?;
+                (v, v, v)
+            }
+            b"CMYK" => {
+                let (c, m, y, k) = (block.f32()?, block.f32()?, block.f32()?, block.f32()?);
+                (
+                    (1.0 - c) * (1.0 - k),
+                    (1.0 - m) * (1.0 - k),
+                    (1.0 - y) * (1.0 - k),
+                )
+            }
+            other => {
+                return Err(invalid_data(format!(
+                    "unsupported colour model `{}`",
+                    String::from_utf8_lossy(other)
+                )))
+            }
+        };
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let colour = Srgb::new(to_u8(r), to_u8(g), to_u8(b));
+
+        if colour_name.eq_ignore_ascii_case(BACKGROUND_NAME) {
+            background = colour;
+        } else {
+            entries.push((colour, 1.0));
+        }
+    }
+
+    Palette::new(name, background, &entries)
+}
+
+struct AseReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> AseReader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let end = self.position + n;
+        let slice = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| invalid_data("unexpected end of file".to_string()))?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
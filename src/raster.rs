@@ -0,0 +1,240 @@
+use nannou::{
+    image::{ImageBuffer, Rgba, RgbaImage},
+    prelude::*,
+};
+
+use crate::mycelium::growth::{Growth, LINE_WEIGHT};
+use crate::simplify::distance_to_segment;
+use crate::smoothing::Smoothing;
+
+/// A CPU stand-in for nannou's renderer, so growths can be drawn without a window or a GPU.
+///
+/// Colours are blended in linear space like nannou's frame texture, and every polyline is
+/// composited once so its own overlapping segments don't darken, matching a tessellated mesh.
+pub struct Raster {
+    width: u32,
+    height: u32,
+    /// Row of the whole image this one starts on, when it's a band of a bigger one.
+    first_row: u32,
+    /// Height of the whole image.
+    image_height: u32,
+    /// Area of the sketch's coordinate space the whole image covers.
+    world: Rect,
+    pixels: Vec<[f32; 3]>,
+    coverage: Vec<f32>,
+    touched: Vec<usize>,
+}
+
+impl Raster {
+    pub fn new(width: u32, height: u32, world: Rect, background: Srgb<u8>) -> Self {
+        Raster::band(width, height, 0, height, world, background)
+    }
+
+    /// `rows` rows of a `width` by `image_height` image of `world`, starting at `first_row`.
+    /// Pixels come out exactly as they would in the same rows of the whole image.
+    pub fn band(
+        width: u32,
+        image_height: u32,
+        first_row: u32,
+        rows: u32,
+        world: Rect,
+        background: Srgb<u8>,
+    ) -> Self {
+        let (r, g, b) = background.into_components();
+        let background = [
+            srgb_to_linear(r as f32 / 255.0),
+            srgb_to_linear(g as f32 / 255.0),
+            srgb_to_linear(b as f32 / 255.0),
+        ];
+        let num_pixels = (width * rows) as usize;
+
+        Raster {
+            width,
+            height: rows,
+            first_row,
+            image_height,
+            world,
+            pixels: vec![background; num_pixels],
+            coverage: vec![0.0; num_pixels],
+            touched: vec![],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// How many pixels one unit of the sketch's coordinates covers along each axis, which differ
+    /// when the image has a different aspect ratio from the world.
+    fn axis_scales(&self) -> Vec2 {
+        vec2(
+            self.width as f32 / self.world.w(),
+            self.image_height as f32 / self.world.h(),
+        )
+    }
+
+    /// How many pixels one unit of stroke weight covers, from the axis the image shrinks more
+    /// along, so stretching an image doesn't fatten its lines.
+    pub fn scale(&self) -> f32 {
+        let scales = self.axis_scales();
+        scales.x.min(scales.y)
+    }
+
+    fn to_pixel(&self, point: Point2) -> Vec2 {
+        vec2(point.x - self.world.left(), self.world.top() - point.y) * self.axis_scales()
+    }
+
+    /// Stroke an anti-aliased polyline `weight` sketch units wide.
+    pub fn stroke_polyline(&mut self, points: &[Point2], weight: f32, colour: Srgba) {
+        let half_width = weight * self.scale() / 2.0;
+        let pixels: Vec<Vec2> = points.iter().map(|p| self.to_pixel(*p)).collect();
+
+        for pair in pixels.windows(2) {
+            self.cover_segment(pair[0], pair[1], half_width);
+        }
+        if let [only] = pixels[..] {
+            self.cover_segment(only, only, half_width);
+        }
+
+        self.composite(colour);
+    }
+
+    fn cover_segment(&mut self, a: Vec2, b: Vec2, half_width: f32) {
+        let reach = half_width + 1.0;
+        let min = a.min(b) - Vec2::splat(reach);
+        let max = a.max(b) + Vec2::splat(reach);
+        let x_range = (min.x.floor().max(0.0) as u32)..(max.x.ceil().min(self.width as f32) as u32);
+        let last_row = self.first_row + self.height;
+        let y_range = (min.y.floor().max(self.first_row as f32) as u32)
+            ..(max.y.ceil().min(last_row as f32) as u32);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let centre = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let coverage =
+                    (half_width + 0.5 - distance_to_segment(centre, a, b)).clamp(0.0, 1.0);
+                if coverage <= 0.0 {
+                    continue;
+                }
+
+                let i = ((y - self.first_row) * self.width + x) as usize;
+                if self.coverage[i] == 0.0 {
+                    self.touched.push(i);
+                }
+                self.coverage[i] = self.coverage[i].max(coverage);
+            }
+        }
+    }
+
+    fn composite(&mut self, colour: Srgba) {
+        let (r, g, b, alpha) = colour.into_components();
+        let source = [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b)];
+
+        for i in self.touched.drain(..) {
+            let a = alpha * self.coverage[i];
+            for (dst, src) in self.pixels[i].iter_mut().zip(source) {
+                *dst = src * a + *dst * (1.0 - a);
+            }
+            self.coverage[i] = 0.0;
+        }
+    }
+
+    pub fn to_rgba8(&self) -> RgbaImage {
+        ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            let [r, g, b] = self.pixels[(y * self.width + x) as usize];
+            let channel = |c: f32| (linear_to_srgb(c) * 255.0).round() as u8;
+            Rgba([channel(r), channel(g), channel(b), u8::MAX])
+        })
+    }
+
+    pub fn to_rgba16(&self) -> ImageBuffer<Rgba<u16>, Vec<u16>> {
+        ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            let [r, g, b] = self.pixels[(y * self.width + x) as usize];
+            let channel = |c: f32| (linear_to_srgb(c) * 65535.0).round() as u16;
+            Rgba([channel(r), channel(g), channel(b), u16::MAX])
+        })
+    }
+}
+
+/// The polylines `Growth::draw` draws, with their colours.
+pub fn growth_polylines(
+    growths: &[Growth],
+    amount: f32,
+    smoothing: &Smoothing,
+) -> Vec<(Vec<Point2>, Srgba)> {
+    growths
+        .iter()
+        .flat_map(|growth| {
+            growth.lines.iter().map(|line| {
+                (
+                    line.draw_points(amount, smoothing),
+                    line.draw_colour(&growth.colour),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Draw `growths` the way `Growth::draw` does onto a `width` by `height` image of `world`.
+pub fn render_growths(
+    growths: &[Growth],
+    width: u32,
+    height: u32,
+    world: Rect,
+    background: Srgb<u8>,
+    amount: f32,
+    smoothing: &Smoothing,
+) -> Raster {
+    let mut raster = Raster::new(width, height, world, background);
+    for (points, colour) in growth_polylines(growths, amount, smoothing) {
+        raster.stroke_polyline(&points, LINE_WEIGHT, colour);
+    }
+    raster
+}
+
+/// Like [`render_growths`] but `band_height` rows at a time, handing each band and the row it
+/// starts on to `write_band`, so huge images don't need a float buffer the size of the whole image.
+#[allow(clippy::too_many_arguments)]
+pub fn render_growths_in_bands(
+    growths: &[Growth],
+    width: u32,
+    height: u32,
+    world: Rect,
+    background: Srgb<u8>,
+    amount: f32,
+    smoothing: &Smoothing,
+    band_height: u32,
+    mut write_band: impl FnMut(u32, Raster),
+) {
+    let polylines = growth_polylines(growths, amount, smoothing);
+
+    for first_row in (0..height).step_by(band_height as usize) {
+        let rows = band_height.min(height - first_row);
+        let mut raster = Raster::band(width, height, first_row, rows, world, background);
+        for (points, colour) in &polylines {
+            raster.stroke_polyline(points, LINE_WEIGHT, *colour);
+        }
+        write_band(first_row, raster);
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.clamp(0.0, 1.0);
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,173 @@
+use nannou::{
+    image::{imageops, Rgba, RgbaImage},
+    text::{font::default_notosans, rt, Font, Scale},
+};
+
+pub const SHEET_BACKGROUND: Rgba<u8> = Rgba([24, 24, 24, 255]);
+pub const LABEL_COLOUR: Rgba<u8> = Rgba([230, 230, 230, 255]);
+pub const LABEL_SIZE: f32 = 16.0;
+/// Space around tiles, and the height of the label under each one.
+pub const PADDING: u32 = 12;
+pub const LABEL_HEIGHT: u32 = 24;
+
+/// Draw `text` with its top left corner at `x`, `y`, blending it into `image`.
+pub fn draw_text(
+    image: &mut RgbaImage,
+    font: &Font,
+    text: &str,
+    (x, y): (i32, i32),
+    size: f32,
+    colour: Rgba<u8>,
+) {
+    let scale = Scale::uniform(size);
+    let ascent = font.v_metrics(scale).ascent;
+
+    for glyph in font.layout(text, scale, rt::point(x as f32, y as f32 + ascent)) {
+        let Some(bounds) = glyph.pixel_bounding_box() else {
+            continue;
+        };
+        glyph.draw(|gx, gy, coverage| {
+            let (px, py) = (bounds.min.x + gx as i32, bounds.min.y + gy as i32);
+            if px < 0 || py < 0 || px >= image.width() as i32 || py >= image.height() as i32 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            for (dst, src) in pixel.0.iter_mut().zip(colour.0).take(3) {
+                *dst = (src as f32 * coverage + *dst as f32 * (1.0 - coverage)).round() as u8;
+            }
+        });
+    }
+}
+
+/// How wide `text` is drawn at `size`.
+pub fn text_width(font: &Font, text: &str, size: f32) -> f32 {
+    font.layout(text, Scale::uniform(size), rt::point(0.0, 0.0))
+        .last()
+        .map_or(0.0, |glyph| {
+            glyph.position().x + glyph.unpositioned().h_metrics().advance_width
+        })
+}
+
+/// The font labels are drawn in.
+pub fn label_font() -> Font {
+    default_notosans()
+}
+
+/// Tiles laid out `columns` to a row, each with its label underneath. Every tile should be the
+/// size of the first.
+pub fn contact_sheet(tiles: &[(RgbaImage, String)], columns: u32) -> RgbaImage {
+    let (tile_w, tile_h) = tiles
+        .first()
+        .map_or((0, 0), |(image, _)| image.dimensions());
+    let columns = columns.max(1);
+    let rows = (tiles.len() as u32).div_ceil(columns);
+    let cell_w = tile_w + PADDING;
+    let cell_h = tile_h + LABEL_HEIGHT + PADDING;
+
+    let mut sheet = RgbaImage::from_pixel(
+        columns * cell_w + PADDING,
+        rows * cell_h + PADDING,
+        SHEET_BACKGROUND,
+    );
+    let font = label_font();
+
+    for (i, (image, label)) in tiles.iter().enumerate() {
+        let x = PADDING + (i as u32 % columns) * cell_w;
+        let y = PADDING + (i as u32 / columns) * cell_h;
+        imageops::replace(&mut sheet, image, x, y);
+        draw_text(
+            &mut sheet,
+            &font,
+            label,
+            (x as i32, (y + tile_h) as i32 + 4),
+            LABEL_SIZE,
+            LABEL_COLOUR,
+        );
+    }
+
+    sheet
+}
+
+/// `cells` in rows of `x_labels.len()`, a row per entry in `y_labels`, with each column's label
+/// along the top under `x_title` and each row's label down the left under `y_title`.
+pub fn grid_sheet(
+    cells: &[RgbaImage],
+    x_title: &str,
+    x_labels: &[String],
+    y_title: &str,
+    y_labels: &[String],
+) -> RgbaImage {
+    let (cell_w, cell_h) = cells.first().map_or((0, 0), |image| image.dimensions());
+    let columns = x_labels.len().max(1) as u32;
+    let rows = y_labels.len() as u32;
+    let font = label_font();
+
+    let widest_label = y_labels
+        .iter()
+        .chain([&y_title.to_string()])
+        .map(|label| text_width(&font, label, LABEL_SIZE).ceil() as u32)
+        .max()
+        .unwrap_or(0);
+    let left = PADDING + widest_label + PADDING;
+    let top = PADDING + 2 * LABEL_HEIGHT;
+
+    let mut sheet = RgbaImage::from_pixel(
+        left + columns * (cell_w + PADDING),
+        top + rows * (cell_h + PADDING),
+        SHEET_BACKGROUND,
+    );
+
+    let x_title_width = text_width(&font, x_title, LABEL_SIZE) as u32;
+    let grid_width = columns * (cell_w + PADDING) - PADDING;
+    let x_title_x = left + grid_width.saturating_sub(x_title_width) / 2;
+    draw_text(
+        &mut sheet,
+        &font,
+        x_title,
+        (x_title_x as i32, PADDING as i32),
+        LABEL_SIZE,
+        LABEL_COLOUR,
+    );
+    draw_text(
+        &mut sheet,
+        &font,
+        y_title,
+        (PADDING as i32, (PADDING + LABEL_HEIGHT) as i32),
+        LABEL_SIZE,
+        LABEL_COLOUR,
+    );
+
+    for (i, label) in x_labels.iter().enumerate() {
+        let x = left + i as u32 * (cell_w + PADDING);
+        let label_x = x + cell_w.saturating_sub(text_width(&font, label, LABEL_SIZE) as u32) / 2;
+        draw_text(
+            &mut sheet,
+            &font,
+            label,
+            (label_x as i32, (PADDING + LABEL_HEIGHT) as i32),
+            LABEL_SIZE,
+            LABEL_COLOUR,
+        );
+    }
+
+    for (j, label) in y_labels.iter().enumerate() {
+        let y = top + j as u32 * (cell_h + PADDING);
+        let label_y = y + cell_h.saturating_sub(LABEL_HEIGHT) / 2;
+        draw_text(
+            &mut sheet,
+            &font,
+            label,
+            (PADDING as i32, label_y as i32),
+            LABEL_SIZE,
+            LABEL_COLOUR,
+        );
+    }
+
+    for (i, cell) in cells.iter().enumerate() {
+        let x = left + (i as u32 % columns) * (cell_w + PADDING);
+        let y = top + (i as u32 / columns) * (cell_h + PADDING);
+        imageops::replace(&mut sheet, cell, x, y);
+    }
+
+    sheet
+}
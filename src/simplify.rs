@@ -0,0 +1,52 @@
+use nannou::prelude::*;
+
+/// Ramer–Douglas–Peucker simplification, keeping only the items whose `position` strays more than
+/// `tolerance` pixels from the straight line between the ones kept either side of it.
+pub fn douglas_peucker<T: Copy>(
+    items: &[T],
+    tolerance: f32,
+    position: impl Fn(&T) -> Point2,
+) -> Vec<T> {
+    if items.len() < 3 {
+        return items.to_vec();
+    }
+
+    let mut keep = vec![false; items.len()];
+    keep[0] = true;
+    keep[items.len() - 1] = true;
+
+    // work through spans with a stack rather than recursing, lines can be thousands of points long
+    let mut spans = vec![(0, items.len() - 1)];
+    while let Some((first, last)) = spans.pop() {
+        let a = position(&items[first]);
+        let b = position(&items[last]);
+
+        let furthest = (first + 1..last)
+            .map(|i| (i, distance_to_segment(position(&items[i]), a, b)))
+            .max_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+
+        if let Some((i, distance)) = furthest {
+            if distance > tolerance {
+                keep[i] = true;
+                spans.push((first, i));
+                spans.push((i, last));
+            }
+        }
+    }
+
+    items
+        .iter()
+        .zip(keep)
+        .filter_map(|(item, keep)| keep.then_some(*item))
+        .collect()
+}
+
+pub fn distance_to_segment(p: Point2, a: Point2, b: Point2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0.0 {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
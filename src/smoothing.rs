@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+use nannou::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Which smoothing stage is applied to a line's points before they're drawn or exported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SmoothingMode {
+    #[default]
+    None,
+    Chaikin,
+    CatmullRom,
+}
+
+impl SmoothingMode {
+    pub fn next_mode(self) -> Self {
+        match self {
+            SmoothingMode::None => SmoothingMode::Chaikin,
+            SmoothingMode::Chaikin => SmoothingMode::CatmullRom,
+            SmoothingMode::CatmullRom => SmoothingMode::None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SmoothingMode::None => "none",
+            SmoothingMode::Chaikin => "chaikin",
+            SmoothingMode::CatmullRom => "catmull-rom",
+        }
+    }
+}
+
+impl FromStr for SmoothingMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [
+            SmoothingMode::None,
+            SmoothingMode::Chaikin,
+            SmoothingMode::CatmullRom,
+        ]
+        .into_iter()
+        .find(|mode| mode.name() == name)
+        .ok_or_else(|| format!("unknown smoothing `{name}`"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Smoothing {
+    None,
+    /// Corner cutting, each iteration doubles the number of points.
+    Chaikin {
+        iterations: u32,
+    },
+    /// A spline through every point, resampled so points are `spacing` pixels apart.
+    CatmullRom {
+        spacing: f32,
+    },
+}
+
+impl Smoothing {
+    pub fn apply(&self, points: Vec<Point2>) -> Vec<Point2> {
+        match *self {
+            Smoothing::None => points,
+            Smoothing::Chaikin { iterations } => chaikin(points, iterations),
+            Smoothing::CatmullRom { spacing } => catmull_rom(&points, spacing),
+        }
+    }
+}
+
+/// Chaikin subdivision of an open polyline, the first and last points are kept in place.
+pub fn chaikin(points: Vec<Point2>, iterations: u32) -> Vec<Point2> {
+    let mut points = points;
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+
+        let mut smoothed = Vec::with_capacity(points.len() * 2);
+        smoothed.push(points[0]);
+        for pair in points.windows(2) {
+            smoothed.push(pair[0].lerp(pair[1], 0.25));
+            smoothed.push(pair[0].lerp(pair[1], 0.75));
+        }
+        smoothed.push(points[points.len() - 1]);
+
+        points = smoothed;
+    }
+    points
+}
+
+/// Uniform Catmull-Rom spline through `points`, resampled at an even `spacing` along its length.
+pub fn catmull_rom(points: &[Point2], spacing: f32) -> Vec<Point2> {
+    if points.len() < 3 || spacing <= 0.0 {
+        return points.to_vec();
+    }
+
+    // evaluate the spline finely enough that resampling it doesn't cut corners
+    let step = spacing / 4.0;
+    let last = points.len() - 1;
+    let mut dense = vec![points[0]];
+    for i in 0..last {
+        let p0 = points[i.saturating_sub(1)];
+        let p1 = points[i];
+        let p2 = points[i + 1];
+        let p3 = points[(i + 2).min(last)];
+
+        let samples = (p1.distance(p2) / step).ceil().max(1.0) as usize;
+        for s in 1..=samples {
+            dense.push(catmull_rom_point(p0, p1, p2, p3, s as f32 / samples as f32));
+        }
+    }
+
+    resample(&dense, spacing)
+}
+
+fn catmull_rom_point(p0: Point2, p1: Point2, p2: Point2, p3: Point2, t: f32) -> Point2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Walk along a polyline dropping a point every `spacing` pixels, always keeping both ends.
+fn resample(points: &[Point2], spacing: f32) -> Vec<Point2> {
+    let mut resampled = vec![points[0]];
+    let mut since_last = 0.0;
+
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let length = a.distance(b);
+        let mut travelled = 0.0;
+
+        while since_last + (length - travelled) >= spacing {
+            travelled += spacing - since_last;
+            resampled.push(a.lerp(b, travelled / length));
+            since_last = 0.0;
+        }
+        since_last += length - travelled;
+    }
+
+    let end = points[points.len() - 1];
+    if resampled.last() != Some(&end) {
+        resampled.push(end);
+    }
+    resampled
+}
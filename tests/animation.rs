@@ -0,0 +1,61 @@
+use liturgy_for_the_mushroom_at_the_end_of_the_world::animation::Curve;
+
+/// Each curve at the start, middle and end of its period.
+fn expected() -> Vec<(Curve, [f32; 3])> {
+    vec![
+        (Curve::Triangle, [1.0, 0.0, 1.0]),
+        (Curve::Sine, [0.0, 1.0, 0.0]),
+        (Curve::EaseInQuad, [0.0, 1.0, 0.0]),
+        (Curve::EaseOutQuad, [0.0, 1.0, 0.0]),
+        (Curve::EaseInOutQuad, [0.0, 1.0, 0.0]),
+        (Curve::EaseInCubic, [0.0, 1.0, 0.0]),
+        (Curve::EaseOutCubic, [0.0, 1.0, 0.0]),
+        (Curve::EaseInOutCubic, [0.0, 1.0, 0.0]),
+        (Curve::EaseInExpo, [0.0, 1.0, 0.0]),
+        (Curve::EaseOutExpo, [0.0, 1.0, 0.0]),
+        (Curve::EaseInOutExpo, [0.0, 1.0, 0.0]),
+        (Curve::Smoothstep, [0.0, 1.0, 0.0]),
+        (Curve::Square, [1.0, 0.0, 1.0]),
+        (Curve::Sawtooth, [0.0, 0.5, 0.0]),
+        (Curve::Bounce, [0.0, 1.0, 0.0]),
+        (
+            Curve::Keyframes(vec![(0.0, 0.2), (0.5, 0.8)]),
+            [0.2, 0.8, 0.2],
+        ),
+    ]
+}
+
+#[test]
+fn curves_hit_their_values_at_period_boundaries() {
+    let expected = expected();
+    for preset in Curve::PRESETS {
+        assert!(
+            expected.iter().any(|(curve, _)| *curve == preset),
+            "{preset:?}"
+        );
+    }
+
+    for (curve, values) in expected {
+        for (t, value) in [0.0, 0.5, 1.0].into_iter().zip(values) {
+            let sample = curve.sample(t);
+            assert!((sample - value).abs() < 1e-5, "{curve:?} at {t}: {sample}");
+        }
+    }
+}
+
+#[test]
+fn curves_repeat_every_period() {
+    for (curve, _) in expected() {
+        for t in [0.1, 0.3, 0.7] {
+            let (a, b, c) = (
+                curve.sample(t),
+                curve.sample(t + 1.0),
+                curve.sample(t - 2.0),
+            );
+            assert!(
+                (a - b).abs() < 1e-4 && (a - c).abs() < 1e-4,
+                "{curve:?} at {t}"
+            );
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{
+        breeding::{cell_at, cell_rect, Breeding, Genome, GRID_SIZE, MUTABLE_KEYS},
+        Config,
+    },
+    palette::Palette,
+};
+use nannou::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn parent() -> Genome {
+    Genome {
+        seed: 1,
+        config: Config::default(),
+    }
+}
+
+#[test]
+fn mutations_stay_within_the_rate() {
+    let parent = parent();
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+
+    for _ in 0..100 {
+        let child = parent.mutate(0.2, &mut rng);
+        for key in MUTABLE_KEYS {
+            let before = parent.config.get_with_default(key, 0.0);
+            let after = child.config.get_with_default(key, 0.0);
+            assert!((after - before).abs() <= before * 0.2 + 1e-4, "{key}");
+        }
+        // anything else is passed down as it is
+        assert_eq!(
+            child.config.get_with_default("max_steps", 0.0),
+            parent.config.get_with_default("max_steps", 0.0)
+        );
+    }
+}
+
+#[test]
+fn choosing_a_cell_breeds_the_next_generation_from_it() {
+    let world = Rect::from_w_h(200.0, 200.0);
+    let mut breeding = Breeding::new(parent(), 0.2, world, Palette::default(), 3, None);
+    assert_eq!(breeding.genomes.len(), GRID_SIZE * GRID_SIZE);
+    assert_eq!(breeding.lineage.len(), 1);
+
+    let chosen = breeding.genomes[0].clone();
+    breeding.choose(0, 0.2);
+
+    assert_eq!(breeding.generation, 1);
+    assert_eq!(breeding.lineage.len(), 2);
+    assert_eq!(breeding.parent().seed, chosen.seed);
+    // the parent stays in the middle of the grid
+    assert!(breeding.genomes.iter().any(|g| g.seed == chosen.seed));
+}
+
+#[test]
+fn lineages_are_saved_from_the_first_parent_on() {
+    let path = std::env::temp_dir().join(format!("lineage-{}.json", std::process::id()));
+    let load =
+        || -> Vec<Genome> { serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap() };
+    let world = Rect::from_w_h(200.0, 200.0);
+    let mut breeding = Breeding::new(
+        parent(),
+        0.2,
+        world,
+        Palette::default(),
+        3,
+        Some(path.clone()),
+    );
+    assert_eq!(breeding.lineage_path(), Some(path.as_path()));
+    assert_eq!(load().len(), 1);
+
+    breeding.choose(4, 0.2);
+    assert_eq!(load().len(), 2);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn clicks_find_the_cell_under_them() {
+    let window = Rect::from_w_h(900.0, 900.0);
+    for i in 0..GRID_SIZE * GRID_SIZE {
+        assert_eq!(cell_at(window, cell_rect(window, i).xy()), Some(i));
+    }
+    assert_eq!(cell_at(window, pt2(-449.0, 449.0)), None);
+    assert!(cell_rect(window, 0).x() < cell_rect(window, 1).x());
+    assert!(cell_rect(window, 0).y() > cell_rect(window, GRID_SIZE).y());
+}
@@ -0,0 +1,128 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    capture::{sink_for_path, Recorder},
+    metadata::read_png_text,
+    mycelium::{create_new_growths, step_growths_serial, Config},
+    palette::Palette,
+    raster::growth_polylines,
+    smoothing::Smoothing,
+};
+use nannou::{
+    geom::Rect,
+    image::{codecs::gif::GifDecoder, AnimationDecoder},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const NUM_FRAMES: u32 = 6;
+
+fn record(extension: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("capture-{}", std::process::id()))
+        .with_extension(extension);
+    let world = Rect::from_w_h(120.0, 80.0);
+    let config = Config::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let mut growths = create_new_growths(world, 4, &Palette::default(), &mut rng);
+
+    let sink = sink_for_path(&path, 25).unwrap();
+    let mut recorder = Recorder::start(sink, 25, 120, 80, world, Palette::default().background);
+    assert_eq!(recorder.frame_duration(), Duration::from_millis(40));
+    for _ in 0..NUM_FRAMES {
+        step_growths_serial(&mut growths, recorder.frame_duration(), &config);
+        recorder
+            .record(growth_polylines(&growths, 1.0, &Smoothing::None))
+            .unwrap();
+    }
+    recorder.finish().unwrap();
+
+    path
+}
+
+#[test]
+fn records_every_frame_to_apng() {
+    let path = record("png");
+    let decoder = png::Decoder::new(fs::File::open(&path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let info = reader.info();
+
+    assert_eq!((info.width, info.height), (120, 80));
+    assert_eq!(info.animation_control().unwrap().num_frames, NUM_FRAMES);
+    let mut frame = vec![0; reader.output_buffer_size()];
+    for _ in 0..NUM_FRAMES {
+        reader.next_frame(&mut frame).unwrap();
+        let control = reader.info().frame_control().unwrap();
+        assert_eq!((control.delay_num, control.delay_den), (1, 25));
+    }
+    assert!(reader.next_frame(&mut frame).is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn empty_recordings_still_make_a_valid_apng_with_text() {
+    let path = std::env::temp_dir().join(format!("capture-empty-{}.png", std::process::id()));
+    let mut sink = sink_for_path(&path, 25).unwrap();
+    let text_chunks = vec![
+        ("Software".to_string(), "mycelium 0.1.0".to_string()),
+        ("Title".to_string(), "苔".to_string()),
+    ];
+    sink.set_text_chunks(text_chunks.clone());
+    sink.finish().unwrap();
+
+    assert_eq!(read_png_text(&path).unwrap(), text_chunks);
+    let decoder = png::Decoder::new(fs::File::open(&path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    // Latin-1 text goes in tEXt where viewers look, anything else in iTXt
+    let info = reader.info();
+    assert_eq!(info.uncompressed_latin1_text[0].keyword, "Software");
+    assert_eq!(info.utf8_text[0].keyword, "Title");
+    assert_eq!(reader.info().animation_control().unwrap().num_frames, 1);
+    let mut frame = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut frame).unwrap();
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn records_every_frame_to_gif() {
+    let path = record("gif");
+    let decoder = GifDecoder::new(fs::File::open(&path).unwrap()).unwrap();
+    let frames = decoder.into_frames().collect_frames().unwrap();
+
+    assert_eq!(frames.len(), NUM_FRAMES as usize);
+    assert_eq!(frames[0].buffer().dimensions(), (120, 80));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_unknown_formats() {
+    let path = std::env::temp_dir().join("capture.mov");
+    assert!(sink_for_path(&path, 30).is_err());
+}
+
+#[test]
+fn streams_y4m_with_a_header() {
+    let path = record("y4m");
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let header = b"YUV4MPEG2 W120 H80 F25:1 Ip A1:1 C444\n";
+    assert!(bytes.starts_with(header));
+    let frame_len = b"FRAME\n".len() + 120 * 80 * 3;
+    assert_eq!(bytes.len(), header.len() + frame_len * NUM_FRAMES as usize);
+    assert!(bytes[header.len()..].starts_with(b"FRAME\n"));
+}
+
+#[test]
+fn streams_raw_rgba_with_a_header() {
+    let path = record("rgba");
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let header = b"RGBA 120 80 25\n";
+    assert!(bytes.starts_with(header));
+    assert_eq!(
+        bytes.len(),
+        header.len() + 120 * 80 * 4 * NUM_FRAMES as usize
+    );
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{
+        clock::{Clock, TICK},
+        create_new_growths,
+        growth::Growth,
+        step_growths_serial, Config,
+    },
+    palette::Palette,
+};
+use nannou::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const FRAME: Duration = Duration::from_nanos(16_666_667);
+
+/// Growths after running `clock` on 60fps frames until `elapsed` of simulation time has passed.
+fn grow_until(mut clock: Clock, elapsed: Duration) -> Vec<Growth> {
+    let config = Config::default();
+    let mut growths = create_new_growths(
+        Rect::from_w_h(300.0, 300.0),
+        5,
+        &Palette::default(),
+        &mut ChaCha8Rng::seed_from_u64(3),
+    );
+    while clock.elapsed() < elapsed {
+        for _ in 0..clock.tick(FRAME).unwrap() {
+            step_growths_serial(&mut growths, TICK, &config);
+        }
+    }
+    assert_eq!(clock.elapsed(), elapsed);
+    growths
+}
+
+#[test]
+fn time_scales_change_the_speed_not_the_result() {
+    let elapsed = TICK * 240;
+    let normal = grow_until(Clock::default(), elapsed);
+
+    let mut half = Clock::default();
+    half.slower();
+    assert_eq!(half.time_scale(), 0.5);
+    let slow = grow_until(half, elapsed);
+
+    assert_eq!(
+        serde_json::to_string(&normal).unwrap(),
+        serde_json::to_string(&slow).unwrap()
+    );
+}
+
+#[test]
+fn slow_time_scales_skip_updates_rather_than_shrinking_ticks() {
+    let mut clock = Clock::default();
+    for _ in 0..3 {
+        clock.slower();
+    }
+    assert_eq!(clock.time_scale(), 0.1);
+    let ticks: u32 = (0..100).map(|_| clock.tick(FRAME).unwrap()).sum();
+    assert_eq!(ticks, 10);
+    assert_eq!(clock.elapsed(), TICK * 10);
+}
+
+#[test]
+fn paused_clocks_only_single_step() {
+    let mut clock = Clock::default();
+    clock.toggle_paused();
+    assert_eq!(clock.tick(FRAME), None);
+    clock.request_step();
+    assert_eq!(clock.tick(FRAME), Some(1));
+    assert_eq!(clock.tick(FRAME), None);
+    assert_eq!(clock.elapsed(), TICK);
+}
@@ -0,0 +1,129 @@
+//! Renders seeded growths with the CPU rasteriser and compares them against the reference images
+//! in `tests/golden`. Run with `UPDATE_GOLDEN=1` to rewrite the references after an intentional
+//! change to the output.
+
+use std::{path::PathBuf, time::Duration};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{create_new_growths, step_growths_serial, Config, WINDOW_SIZE},
+    palette::Palette,
+    raster::render_growths,
+    smoothing::Smoothing,
+};
+use nannou::{
+    geom::Rect,
+    image::{self, RgbaImage},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const SIZE: u32 = 300;
+const STEPS: usize = 240;
+const DT: Duration = Duration::from_millis(16);
+
+/// Largest difference (0-255) any channel of a pixel can have before the pixel counts as changed.
+const PIXEL_TOLERANCE: u8 = 12;
+/// Fraction of pixels allowed to change before the images are considered different.
+const CHANGED_PIXELS_TOLERANCE: f32 = 0.002;
+
+fn render(seed: u64, vary_amount: f32, smoothing: Smoothing) -> RgbaImage {
+    let config = Config::default();
+    let palette = Palette::default();
+    let world = Rect::from_w_h(WINDOW_SIZE, WINDOW_SIZE);
+
+    let mut growths = create_new_growths(world, 8, &palette, &mut ChaCha8Rng::seed_from_u64(seed));
+    for _ in 0..STEPS {
+        step_growths_serial(&mut growths, DT, &config);
+    }
+
+    render_growths(
+        &growths,
+        SIZE,
+        SIZE,
+        world,
+        palette.background,
+        vary_amount,
+        &smoothing,
+    )
+    .to_rgba8()
+}
+
+/// Fraction of pixels where any channel of `actual` is further than [`PIXEL_TOLERANCE`] from
+/// `expected`, so a change of hue counts even when the brightness stays the same.
+fn changed_fraction(expected: &RgbaImage, actual: &RgbaImage) -> f32 {
+    let changed = expected
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(e, a)| {
+            e.0.iter()
+                .zip(a.0)
+                .any(|(e, a)| e.abs_diff(a) > PIXEL_TOLERANCE)
+        })
+        .count();
+    changed as f32 / (expected.width() * expected.height()) as f32
+}
+
+fn assert_matches_golden(name: &str, actual: &RgbaImage) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name)
+        .with_extension("png");
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save(&path).unwrap();
+        return;
+    }
+
+    let expected = image::open(&path)
+        .unwrap_or_else(|e| panic!("failed to open {}: {e}", path.display()))
+        .to_rgba8();
+    assert_eq!(expected.dimensions(), actual.dimensions());
+
+    let changed_fraction = changed_fraction(&expected, actual);
+    if changed_fraction > CHANGED_PIXELS_TOLERANCE {
+        let failed_path = path.with_extension("actual.png");
+        actual.save(&failed_path).unwrap();
+        panic!(
+            "{name}: {:.2}% of pixels differ from the reference, output saved to {}",
+            changed_fraction * 100.0,
+            failed_path.display()
+        );
+    }
+}
+
+#[test]
+fn growths_match_golden() {
+    assert_matches_golden("growths", &render(1, 1.0, Smoothing::None));
+}
+
+#[test]
+fn varied_growths_match_golden() {
+    assert_matches_golden("growths_varied", &render(2, 6.0, Smoothing::None));
+}
+
+#[test]
+fn smoothed_growths_match_golden() {
+    assert_matches_golden(
+        "growths_catmull_rom",
+        &render(1, 1.0, Smoothing::CatmullRom { spacing: 4.0 }),
+    );
+}
+
+#[test]
+fn changes_of_hue_at_the_same_brightness_count() {
+    let grey = RgbaImage::from_pixel(4, 4, image::Rgba([128, 128, 128, 255]));
+    // about the same luma as the grey
+    let purple = RgbaImage::from_pixel(4, 4, image::Rgba([200, 99, 200, 255]));
+
+    assert_eq!(changed_fraction(&grey, &grey), 0.0);
+    assert_eq!(changed_fraction(&grey, &purple), 1.0);
+}
+
+#[test]
+fn render_is_deterministic_for_a_seed() {
+    assert_eq!(
+        render(3, 1.0, Smoothing::None),
+        render(3, 1.0, Smoothing::None)
+    );
+}
@@ -0,0 +1,93 @@
+use std::fs;
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    metadata::{read_png_text, save_png, CaptureInfo},
+    mycelium::Config,
+};
+use nannou::image::{GenericImageView, RgbaImage};
+
+/// The keywords of the PNG at `path`'s `tEXt` chunks.
+fn latin1_keywords(path: &std::path::Path) -> Vec<String> {
+    let reader = png::Decoder::new(fs::File::open(path).unwrap())
+        .read_info()
+        .unwrap();
+    reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| chunk.keyword.clone())
+        .collect()
+}
+
+fn info() -> CaptureInfo {
+    let mut info = CaptureInfo::new("mycelium", 42, &Config::default(), (32, 16));
+    info.palette = "moss".to_string();
+    info.fps = Some(30);
+    info
+}
+
+#[test]
+fn pngs_carry_the_capture_info() {
+    let path = std::env::temp_dir().join(format!("metadata-{}.png", std::process::id()));
+    let info = info();
+    save_png(&RgbaImage::new(32, 16), &path, &info.text_chunks()).unwrap();
+    let text = read_png_text(&path).unwrap();
+    let image = nannou::image::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!((image.width(), image.height()), (32, 16));
+    let find = |keyword: &str| {
+        text.iter()
+            .find(|(k, _)| k == keyword)
+            .map(|(_, t)| t.clone())
+            .unwrap()
+    };
+    assert_eq!(find("Seed"), "42");
+    let embedded: CaptureInfo = serde_json::from_str(&find("Comment")).unwrap();
+    assert_eq!(embedded, info);
+    assert_eq!(embedded.config["step_amount"], 8.0);
+}
+
+#[test]
+fn manifests_round_trip() {
+    let path = std::env::temp_dir().join(format!("manifest-{}.json", std::process::id()));
+    let info = info();
+    info.write_manifest(&path).unwrap();
+    let loaded = CaptureInfo::load_manifest(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, info);
+}
+
+#[test]
+fn info_that_isnt_latin_1_stays_in_text_chunks_viewers_read() {
+    let path = std::env::temp_dir().join(format!("metadata-utf8-{}.png", std::process::id()));
+    let mut info = info();
+    info.palette = "苔 moss 🍄".to_string();
+    save_png(&RgbaImage::new(4, 4), &path, &info.text_chunks()).unwrap();
+    let text = read_png_text(&path).unwrap();
+    let keywords = latin1_keywords(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(keywords, ["Software", "Seed", "Comment"]);
+    let (_, comment) = text.iter().find(|(k, _)| k == "Comment").unwrap();
+    assert!(comment.is_ascii());
+    let embedded: CaptureInfo = serde_json::from_str(comment).unwrap();
+    assert_eq!(embedded.palette, "苔 moss 🍄");
+}
+
+#[test]
+fn text_that_isnt_latin_1_goes_in_itxt_chunks() {
+    let path = std::env::temp_dir().join(format!("metadata-itxt-{}.png", std::process::id()));
+    let chunks = [
+        ("Title".to_string(), "café".to_string()),
+        ("Frame".to_string(), "苔".to_string()),
+    ];
+    save_png(&RgbaImage::new(4, 4), &path, &chunks).unwrap();
+    let text = read_png_text(&path).unwrap();
+    let keywords = latin1_keywords(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(keywords, ["Title"]);
+    assert_eq!(text, chunks);
+}
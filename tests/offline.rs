@@ -0,0 +1,109 @@
+use std::{fs, time::Duration};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{
+        clock::{Clock, TICK},
+        simulation::Simulation,
+        timeline::Timeline,
+        Config,
+    },
+    palette::Palette,
+};
+use nannou::geom::Rect;
+
+fn new_simulation(seed: u64, config: Config) -> Simulation {
+    Simulation::new(
+        seed,
+        Rect::from_w_h(300.0, 300.0),
+        config,
+        Palette::default(),
+        Timeline::default(),
+    )
+    .start()
+}
+
+fn simulate(seed: u64, config: Config) -> Simulation {
+    let mut simulation = new_simulation(seed, config);
+    simulation.step_ticks(60);
+    simulation
+}
+
+/// Step a new simulation of `seed` the way `animate` does, for `num_frames` frames at `fps`.
+fn animate(seed: u64, fps: u32, num_frames: u32) -> Simulation {
+    let mut simulation = new_simulation(seed, Config::default());
+    let mut clock = Clock::at_fps(fps);
+    for _ in 0..num_frames {
+        simulation.step_ticks(clock.tick(Duration::ZERO).unwrap_or_default());
+    }
+    simulation
+}
+
+#[test]
+fn same_seed_and_config_render_the_same_frames() {
+    let a = simulate(11, Config::default());
+    let b = simulate(11, Config::default());
+
+    assert_eq!(a.elapsed(), TICK * 60);
+    assert_eq!(a.vary_amount, b.vary_amount);
+    assert_eq!(a.render(150, 150).to_rgba8(), b.render(150, 150).to_rgba8());
+}
+
+#[test]
+fn frame_rates_change_how_often_frames_are_taken_not_what_grows() {
+    let slow = animate(5, 30, 60);
+    let fast = animate(5, 60, 120);
+
+    assert_eq!(slow.elapsed(), TICK * 120);
+    assert_eq!(fast.elapsed(), slow.elapsed());
+    assert_eq!(
+        serde_json::to_string(&slow.growths).unwrap(),
+        serde_json::to_string(&fast.growths).unwrap()
+    );
+}
+
+#[test]
+fn frames_longer_than_the_tick_cap_still_run_every_tick() {
+    assert_eq!(animate(5, 1, 2).elapsed(), TICK * 120);
+}
+
+#[test]
+fn presets_override_defaults() {
+    let path = std::env::temp_dir().join(format!("preset-{}.txt", std::process::id()));
+    fs::write(
+        &path,
+        "// slower\nstep_amount = 3\n\nnew_key=2.5 // added\n",
+    )
+    .unwrap();
+    let config = Config::load_preset(&path).unwrap();
+
+    fs::write(&path, "step_amount 3\n").unwrap();
+    let malformed = Config::load_preset(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(config.get_with_default("step_amount", 0.0), 3.0);
+    assert_eq!(config.get_with_default("new_key", 0.0), 2.5);
+    assert_eq!(
+        config.get_with_default("rand_factor", 0.0),
+        Config::default().get_with_default("rand_factor", 0.0)
+    );
+    assert!(malformed.is_err());
+}
+
+#[test]
+fn configs_whose_keys_and_values_disagree_load_consistently() {
+    let config: Config = serde_json::from_str(
+        r#"{"values": {"step_amount": 3.0, "rand_factor": 2.0, "competition": 1.0},
+            "keys": ["missing", "rand_factor", "rand_factor", "step_amount"]}"#,
+    )
+    .unwrap();
+
+    let values: Vec<(&str, f32)> = config.values().collect();
+    assert_eq!(
+        values,
+        [
+            ("rand_factor", 2.0),
+            ("step_amount", 3.0),
+            ("competition", 1.0)
+        ]
+    );
+}
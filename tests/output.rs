@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    cli::Args,
+    output::{FrameNames, DEFAULT_TEMPLATE},
+};
+
+fn args(args: &[&str]) -> Args {
+    Args::parse(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+}
+
+#[test]
+fn templates_fill_in_every_token() {
+    let mut names =
+        FrameNames::new(Path::new("out"), "{sketch}/{preset}-{seed}/{frame}.png").unwrap();
+    names.set("sketch", "mycelium");
+    names.set("seed", "42");
+    names.set("preset", "moss");
+
+    assert_eq!(
+        names.frame_path(7),
+        Path::new("out/mycelium/moss-42/00007.png")
+    );
+    assert_eq!(names.capture_dir(), Path::new("out/mycelium/moss-42"));
+}
+
+#[test]
+fn missing_values_leave_no_dangling_separators() {
+    let mut names = FrameNames::new(Path::new("out"), "{preset}-{seed}/{frame}.png").unwrap();
+    names.set("seed", "3");
+    assert_eq!(names.frame_path(1), Path::new("out/3/00001.png"));
+
+    let mut names = FrameNames::new(Path::new("out"), "a_{seed}-{preset}_b/{frame}.png").unwrap();
+    assert_eq!(names.frame_path(1), Path::new("out/a_b/00001.png"));
+    names.set("seed", "3");
+    assert_eq!(names.frame_path(1), Path::new("out/a_3_b/00001.png"));
+}
+
+#[test]
+fn separators_the_template_spells_out_are_kept() {
+    let names = FrameNames::new(Path::new("out"), "out_/-x-{frame}_.png").unwrap();
+    assert_eq!(names.frame_path(4), Path::new("out/out_/-x-00004_.png"));
+
+    let names = FrameNames::new(Path::new("out"), "_{frame}-{seed}").unwrap();
+    assert_eq!(names.frame_path(4), Path::new("out/_00004"));
+}
+
+#[test]
+fn dates_have_no_colons() {
+    let names = FrameNames::new(Path::new("out"), DEFAULT_TEMPLATE).unwrap();
+    assert!(!names.frame_path(1).to_string_lossy().contains(':'));
+}
+
+#[test]
+fn bad_templates_are_rejected() {
+    assert!(FrameNames::new(Path::new("out"), "{sketch}/{nope}/{frame}.png").is_err());
+    assert!(FrameNames::new(Path::new("out"), "{sketch}/still.png").is_err());
+}
+
+#[test]
+fn args_set_the_root_template_and_preset() {
+    let args = args(&[
+        "--output-root",
+        "/tmp/renders",
+        "--output-template",
+        "{preset}/{frame}.png",
+        "--preset",
+        "presets/slow.txt",
+    ]);
+    let names = FrameNames::from_args(&args, Path::new("output"), "mycelium").unwrap();
+    assert_eq!(
+        names.frame_path(2),
+        Path::new("/tmp/renders/slow/00002.png")
+    );
+
+    let defaults =
+        FrameNames::from_args(&Args::default(), Path::new("output"), "mycelium").unwrap();
+    assert!(defaults.frame_path(1).starts_with("output/mycelium"));
+}
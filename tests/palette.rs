@@ -0,0 +1,165 @@
+use std::{fs, io, path::Path};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::palette::Palette;
+use nannou::prelude::*;
+
+/// Load `contents` as a palette file called `name`.
+fn load(name: &str, contents: &[u8]) -> io::Result<Palette> {
+    let dir = std::env::temp_dir().join(format!("palettes-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    let palette = Palette::load(&path);
+    fs::remove_file(&path).unwrap();
+    palette
+}
+
+/// An `.ase` colour entry block called `name`, in `model` with `values`.
+fn ase_colour(name: &str, model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+    let name: Vec<u16> = name.encode_utf16().chain([0]).collect();
+    let mut block = (name.len() as u16).to_be_bytes().to_vec();
+    block.extend(name.iter().flat_map(|unit| unit.to_be_bytes()));
+    block.extend(model);
+    block.extend(values.iter().flat_map(|v| v.to_be_bytes()));
+    // global colour type
+    block.extend(0u16.to_be_bytes());
+
+    let mut entry = 0x0001u16.to_be_bytes().to_vec();
+    entry.extend((block.len() as u32).to_be_bytes());
+    entry.extend(block);
+    entry
+}
+
+/// An `.ase` file of `blocks`.
+fn ase(blocks: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = b"ASEF".to_vec();
+    bytes.extend([0, 1, 0, 0]);
+    bytes.extend((blocks.len() as u32).to_be_bytes());
+    bytes.extend(blocks.concat());
+    bytes
+}
+
+#[test]
+fn hex_lists_load_with_weights_and_a_background() {
+    let palette = Palette::load(Path::new("palettes/dusk.txt")).unwrap();
+
+    assert_eq!(palette.name, "dusk");
+    assert_eq!(palette.background, Srgb::new(0x1B, 0x17, 0x25));
+    assert_eq!(palette.colours().len(), 4);
+    assert_eq!(palette.colours()[0], Srgb::new(0x53, 0x4B, 0x62));
+}
+
+#[test]
+fn bad_hex_lists_are_rejected() {
+    for contents in [
+        "",
+        "// only a comment",
+        "#12345",
+        "#GGGGGG",
+        "#123456 heavy",
+        "#123456 0",
+        "#123456 -1",
+        "background",
+        "background #12\n#123456",
+    ] {
+        assert!(load("bad.txt", contents.as_bytes()).is_err(), "{contents}");
+    }
+}
+
+#[test]
+fn gimp_palettes_load_their_name_and_background() {
+    let palette = Palette::load(Path::new("palettes/moss.gpl")).unwrap();
+
+    assert_eq!(palette.name, "Moss");
+    assert_eq!(palette.background, Srgb::new(20, 26, 19));
+    assert_eq!(palette.colours().len(), 4);
+    assert_eq!(palette.colours()[0], Srgb::new(88, 129, 87));
+}
+
+#[test]
+fn gimp_colours_dont_need_names() {
+    let palette = load("plain.gpl", b"GIMP Palette\n1 2 3\n  4 5 6\t\n").unwrap();
+
+    assert_eq!(palette.name, "plain");
+    assert_eq!(palette.background, Srgb::new(0, 0, 0));
+    assert_eq!(palette.colours(), [Srgb::new(1, 2, 3), Srgb::new(4, 5, 6)]);
+}
+
+#[test]
+fn bad_gimp_palettes_are_rejected() {
+    for contents in [
+        "1 2 3 Red",
+        "GIMP Palette\n",
+        "GIMP Palette\n1 2 background",
+        "GIMP Palette\n1 2",
+        "GIMP Palette\n1 2 300 Red",
+        "GIMP Palette\nred green blue",
+        "GIMP Palette\n1 2 3 background",
+    ] {
+        assert!(load("bad.gpl", contents.as_bytes()).is_err(), "{contents}");
+    }
+}
+
+#[test]
+fn swatch_exchange_files_load_every_colour_model() {
+    let group_start = [0xC0, 0x01, 0, 0, 0, 4, 0, 1, 0, 0].to_vec();
+    let group_end = [0xC0, 0x02, 0, 0, 0, 0].to_vec();
+    let bytes = ase(&[
+        group_start,
+        ase_colour("Background", b"RGB ", &[0.1, 0.2, 0.3]),
+        ase_colour("苔", b"RGB ", &[1.0, 0.5, 0.0]),
+        ase_colour("Grey", b"Gray", &[0.5]),
+        ase_colour("Cyan", b"CMYK", &[1.0, 0.0, 0.0, 0.0]),
+        group_end,
+    ]);
+    let palette = load("swatches.ase", &bytes).unwrap();
+
+    assert_eq!(palette.name, "swatches");
+    assert_eq!(palette.background, Srgb::new(26, 51, 77));
+    assert_eq!(
+        palette.colours(),
+        [
+            Srgb::new(255, 128, 0),
+            Srgb::new(128, 128, 128),
+            Srgb::new(0, 255, 255)
+        ]
+    );
+}
+
+#[test]
+fn bad_swatch_exchange_files_are_rejected() {
+    let red = ase_colour("Red", b"RGB ", &[1.0, 0.0, 0.0]);
+    let just_red = || ase(std::slice::from_ref(&red));
+
+    let mut wrong_header = just_red();
+    wrong_header[..4].copy_from_slice(b"ASEX");
+
+    let mut missing_block = just_red();
+    missing_block[8..12].copy_from_slice(&2u32.to_be_bytes());
+
+    let mut past_the_end = just_red();
+    let length = red.len() as u32 - 6;
+    past_the_end[14..18].copy_from_slice(&(length + 1).to_be_bytes());
+
+    let mut too_short = just_red();
+    too_short[14..18].copy_from_slice(&(length - 8).to_be_bytes());
+
+    let mut truncated = just_red();
+    truncated.truncate(truncated.len() - 3);
+
+    for (why, bytes) in [
+        ("empty", vec![]),
+        ("wrong header", wrong_header),
+        ("no colours", ase(&[])),
+        ("missing block", missing_block),
+        ("block past the end", past_the_end),
+        ("block shorter than its colour", too_short),
+        ("truncated", truncated),
+        (
+            "unknown model",
+            ase(&[ase_colour("Lab", b"LAB ", &[50.0, 0.0, 0.0])]),
+        ),
+    ] {
+        assert!(load("bad.ase", &bytes).is_err(), "{why}");
+    }
+}
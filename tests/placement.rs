@@ -0,0 +1,143 @@
+use std::{fs, sync::Arc};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::mycelium::{
+    placement::{parse_csv, parse_json, Placement, PlacementFiles, PlacementMode},
+    Config,
+};
+use nannou::{
+    image::{GrayImage, Luma},
+    prelude::*,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn window() -> Rect {
+    Rect::from_w_h(900.0, 600.0)
+}
+
+fn centres(placement: &Placement, n: u64) -> Vec<Point2> {
+    placement.centres(window(), n, &mut ChaCha8Rng::seed_from_u64(1))
+}
+
+#[test]
+fn modes_parse_from_their_names() {
+    let mut mode = PlacementMode::default();
+    loop {
+        assert_eq!(mode.name().parse::<PlacementMode>(), Ok(mode));
+        mode = mode.next_mode();
+        if mode == PlacementMode::default() {
+            break;
+        }
+    }
+    assert!("scatter".parse::<PlacementMode>().is_err());
+}
+
+#[test]
+fn poisson_disk_keeps_centres_apart() {
+    let points = centres(&Placement::PoissonDisk { min_distance: 80.0 }, 20);
+    assert_eq!(points.len(), 20);
+    for (i, a) in points.iter().enumerate() {
+        assert!(window().contains(*a));
+        for b in &points[i + 1..] {
+            assert!(a.distance(*b) >= 80.0);
+        }
+    }
+}
+
+#[test]
+fn jittered_grid_has_one_centre_per_cell() {
+    let points = centres(&Placement::JitteredGrid { jitter: 0.0 }, 6);
+    assert_eq!(
+        points,
+        [
+            vec2(-300.0, 150.0),
+            vec2(0.0, 150.0),
+            vec2(300.0, 150.0),
+            vec2(-300.0, -150.0),
+            vec2(0.0, -150.0),
+            vec2(300.0, -150.0),
+        ]
+    );
+}
+
+#[test]
+fn jittered_grid_rows_share_centres_evenly() {
+    let square = Rect::from_w_h(900.0, 900.0);
+    let points = Placement::JitteredGrid { jitter: 0.0 }.centres(
+        square,
+        50,
+        &mut ChaCha8Rng::seed_from_u64(1),
+    );
+    assert_eq!(points.len(), 50);
+
+    let mut rows: Vec<(f32, usize)> = vec![];
+    for point in &points {
+        match rows.iter_mut().find(|(y, _)| (*y - point.y).abs() < 1e-3) {
+            Some((_, count)) => *count += 1,
+            None => rows.push((point.y, 1)),
+        }
+    }
+    assert_eq!(rows.len(), 7);
+    assert!(rows.iter().all(|(_, count)| *count == 7 || *count == 8));
+    // the last row reaches across the window like the others
+    let bottom = rows.last().unwrap().0;
+    let bottom_xs = points
+        .iter()
+        .filter(|p| (p.y - bottom).abs() < 1e-3)
+        .map(|p| p.x);
+    assert!(bottom_xs.clone().fold(f32::MAX, f32::min) < -300.0);
+    assert!(bottom_xs.fold(f32::MIN, f32::max) > 300.0);
+}
+
+#[test]
+fn rings_and_spirals_stay_within_their_radius() {
+    let ring = centres(&Placement::Ring { radius: 0.5 }, 12);
+    assert!(ring.iter().all(|p| (p.length() - 150.0).abs() < 1e-3));
+
+    let spiral = centres(&Placement::Spiral { radius: 0.5 }, 50);
+    assert!(spiral.iter().all(|p| p.length() <= 150.0));
+    assert!(spiral[0].length() < spiral[49].length());
+}
+
+#[test]
+fn points_files_are_fractions_of_the_window() {
+    let csv = parse_csv("x, y\n0, 0 // top left\n0.5,0.5\n\n1, 1\n").unwrap();
+    let json = parse_json(r#"[[0, 0], {"x": 0.5, "y": 0.5}, [1, 1]]"#).unwrap();
+    assert_eq!(csv, json);
+    assert!(parse_csv("0, 0\nnope\n").is_err());
+
+    let points = centres(&Placement::Points(Arc::new(csv)), 20);
+    assert_eq!(
+        points,
+        [vec2(-450.0, 300.0), vec2(0.0, 0.0), vec2(450.0, -300.0)]
+    );
+}
+
+#[test]
+fn density_places_centres_in_the_bright_parts() {
+    // only the right half is lit
+    let image = GrayImage::from_fn(10, 10, |x, _| Luma([if x >= 5 { 255 } else { 0 }]));
+    let points = centres(&Placement::Density(Arc::new(image)), 30);
+    assert!(points.iter().all(|p| p.x >= 0.0));
+}
+
+#[test]
+fn file_modes_need_their_file() {
+    let mut files = PlacementFiles::default();
+    let config = Config::default();
+    assert!(!files.has(PlacementMode::File));
+    assert!(matches!(
+        files.placement(PlacementMode::File, &config),
+        Placement::Uniform { .. }
+    ));
+
+    let path = std::env::temp_dir().join(format!("centres-{}.csv", std::process::id()));
+    fs::write(&path, "0.25, 0.75\n").unwrap();
+    assert_eq!(files.load(&path).unwrap(), PlacementMode::File);
+    fs::remove_file(&path).unwrap();
+    assert!(files.has(PlacementMode::File));
+    assert!(matches!(
+        files.placement(PlacementMode::File, &config),
+        Placement::Points(_)
+    ));
+}
@@ -0,0 +1,149 @@
+use std::{collections::HashMap, time::Duration};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::mycelium::{
+    growth::{Line, LineState, Point},
+    Config,
+};
+use nannou::prelude::*;
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn config(step_amount: f32, rand_factor: f32, max_steps: f32, stuck_steps: f32) -> Config {
+    Config::new(HashMap::from([
+        ("step_amount".to_string(), step_amount),
+        ("rand_factor".to_string(), rand_factor),
+        ("max_steps".to_string(), max_steps),
+        ("stuck_steps".to_string(), stuck_steps),
+    ]))
+}
+
+fn coordinate() -> impl Strategy<Value = f32> {
+    -1000.0f32..1000.0
+}
+
+fn vec2_strategy() -> impl Strategy<Value = Vec2> {
+    (coordinate(), coordinate()).prop_map(|(x, y)| vec2(x, y))
+}
+
+/// Variations are unit vectors, or zero for a line's start and end points.
+fn variation() -> impl Strategy<Value = Vec2> {
+    prop_oneof![
+        Just(Vec2::ZERO),
+        (0.0f32..TAU).prop_map(|angle| vec2(angle.cos(), angle.sin())),
+    ]
+}
+
+fn point() -> impl Strategy<Value = Point> {
+    (vec2_strategy(), variation()).prop_map(|(p, v)| Point::with_variation(p, v))
+}
+
+fn is_finite(point: Point) -> bool {
+    let p: Point2 = point.into();
+    p.is_finite() && point.variation().is_finite()
+}
+
+proptest! {
+    #[test]
+    fn point_arithmetic_never_produces_nan(a in point(), b in point(), v in vec2_strategy(), f in -10.0f32..10.0) {
+        prop_assert!(is_finite(a + b));
+        prop_assert!(is_finite(a - b));
+        prop_assert!(is_finite(a * b));
+        prop_assert!(is_finite(a + v));
+        prop_assert!(is_finite(a - v));
+        prop_assert!(is_finite(a * v));
+        prop_assert!(is_finite(a * f));
+        prop_assert!(is_finite(a.normalize()));
+    }
+
+    #[test]
+    fn combined_variations_stay_unit_or_zero(a in point(), b in point()) {
+        for p in [a + b, a - b, a * b] {
+            let length = p.variation().length();
+            prop_assert!(length == 0.0 || (length - 1.0).abs() < 1e-4, "length {length}");
+        }
+    }
+
+    #[test]
+    fn position_arithmetic_matches_vec2(a in point(), b in point(), v in vec2_strategy()) {
+        let (pa, pb): (Point2, Point2) = (a.into(), b.into());
+        prop_assert_eq!(Point2::from(a + b), pa + pb);
+        prop_assert_eq!(Point2::from(a - b), pa - pb);
+        prop_assert_eq!(Point2::from(a + v), pa + v);
+        prop_assert_eq!((a + v).variation(), a.variation());
+    }
+
+    #[test]
+    fn vary_by_amount_stays_within_amount(p in vec2_strategy(), seed in any::<u64>(), amount in -20.0f32..20.0) {
+        let point = Point::new_with_rng(p, &mut ChaCha8Rng::seed_from_u64(seed));
+        let varied = point.vary_by_amount(amount);
+
+        prop_assert!(varied.is_finite());
+        prop_assert!(varied.distance(p) <= amount.abs() + 1e-3);
+        prop_assert_eq!(point.vary_by_amount(0.0), p);
+    }
+
+    #[test]
+    fn every_line_terminates_within_max_steps(
+        start in vec2_strategy(),
+        end in vec2_strategy(),
+        seed in any::<u64>(),
+        step_amount in 0.0f32..16.0,
+        rand_factor in 0.0f32..50.0,
+        dt_millis in 1u64..50,
+        max_steps in 1u32..500,
+    ) {
+        let config = config(step_amount, rand_factor, max_steps as f32, max_steps as f32);
+        let mut line = Line::new(start, end, ChaCha8Rng::seed_from_u64(seed));
+
+        for _ in 0..=max_steps {
+            line.step_line(Duration::from_millis(dt_millis), &config);
+        }
+
+        prop_assert!(line.is_finished());
+        prop_assert!(line.steps <= max_steps);
+        prop_assert!(line.points.iter().all(|p| is_finite(*p)));
+        if line.state == LineState::Arrived {
+            prop_assert_eq!(Point2::from(*line.points.last().unwrap()), end);
+        }
+    }
+
+    #[test]
+    fn lines_without_randomness_arrive(
+        start in vec2_strategy(),
+        end in vec2_strategy(),
+        seed in any::<u64>(),
+        step_amount in 1.0f32..16.0,
+        dt_millis in 10u64..50,
+    ) {
+        let config = config(step_amount, 0.0, f32::MAX, f32::MAX);
+        let mut line = Line::new(start, end, ChaCha8Rng::seed_from_u64(seed));
+
+        // every step moves `2 * dt * step_amount` straight towards the end
+        let step_length = 2.0 * step_amount * dt_millis as f32 / 1000.0;
+        let max_steps = (start.distance(end) / step_length).ceil() as u32 + 2;
+        for _ in 0..max_steps {
+            line.step_line(Duration::from_millis(dt_millis), &config);
+        }
+
+        prop_assert_eq!(line.state, LineState::Arrived);
+    }
+
+    #[test]
+    fn lines_that_stop_moving_get_stuck(
+        start in vec2_strategy(),
+        end in vec2_strategy(),
+        seed in any::<u64>(),
+        stuck_steps in 1u32..200,
+    ) {
+        prop_assume!(start.distance(end) > 4.0);
+        let config = config(0.0, 0.0, f32::MAX, stuck_steps as f32);
+        let mut line = Line::new(start, end, ChaCha8Rng::seed_from_u64(seed));
+
+        for _ in 0..=stuck_steps {
+            line.step_line(Duration::from_millis(16), &config);
+        }
+
+        prop_assert_eq!(line.state, LineState::Stuck);
+    }
+}
@@ -0,0 +1,35 @@
+use liturgy_for_the_mushroom_at_the_end_of_the_world::raster::Raster;
+use nannou::prelude::*;
+
+/// Pixels in `row` of `raster` that aren't the black background.
+fn lit_in_row(raster: &Raster, row: u32) -> Vec<u32> {
+    let image = raster.to_rgba8();
+    (0..image.width())
+        .filter(|x| image.get_pixel(*x, row).0[0] > 0)
+        .collect()
+}
+
+#[test]
+fn stretched_images_keep_positions_per_axis_and_strokes_by_the_narrower_one() {
+    // four pixels per unit across, one down
+    let mut raster = Raster::new(400, 100, Rect::from_w_h(100.0, 100.0), Srgb::new(0, 0, 0));
+    assert_eq!(raster.scale(), 1.0);
+
+    let white = Srgba::new(1.0, 1.0, 1.0, 1.0);
+    raster.stroke_polyline(&[pt2(0.0, 40.0), pt2(0.0, -40.0)], 4.0, white);
+    raster.stroke_polyline(&[pt2(-40.0, 25.0), pt2(40.0, 25.0)], 4.0, white);
+
+    // the vertical line is as many pixels wide as the horizontal one is tall
+    let across = lit_in_row(&raster, 60);
+    assert!((4..=6).contains(&across.len()), "{across:?}");
+    assert!(across.iter().all(|x| (196..=204).contains(x)), "{across:?}");
+    let image = raster.to_rgba8();
+    let down: Vec<u32> = (0..100)
+        .filter(|y| image.get_pixel(50, *y).0[0] > 0)
+        .collect();
+    assert!((4..=6).contains(&down.len()), "{down:?}");
+    assert!(down.iter().all(|y| (21..=29).contains(y)), "{down:?}");
+    // and reaches 40 units either side, at four pixels a unit
+    let row = lit_in_row(&raster, 25);
+    assert_eq!((row[0], row[row.len() - 1]), (38, 361));
+}
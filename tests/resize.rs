@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{
+        create_new_growths,
+        resize::{rescale_growths, rescale_point, ResizeMode},
+        simulation::Simulation,
+        step_growths_serial,
+        timeline::Timeline,
+        Config,
+    },
+    palette::Palette,
+};
+use nannou::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn modes_parse_from_their_names() {
+    for mode in [ResizeMode::Rescale, ResizeMode::Regrow] {
+        assert_eq!(mode.name().parse::<ResizeMode>(), Ok(mode));
+        assert_ne!(mode.next_mode(), mode);
+    }
+    assert!("stretch".parse::<ResizeMode>().is_err());
+}
+
+#[test]
+fn points_keep_their_place_relative_to_the_window() {
+    let from = Rect::from_w_h(900.0, 900.0);
+    let to = Rect::from_w_h(1920.0, 1080.0);
+    for (point, expected) in [
+        (vec2(-450.0, 450.0), vec2(-960.0, 540.0)),
+        (vec2(450.0, -450.0), vec2(960.0, -540.0)),
+        (vec2(225.0, -450.0), vec2(480.0, -540.0)),
+    ] {
+        assert!(rescale_point(point, from, to).distance(expected) < 1e-3);
+    }
+}
+
+#[test]
+fn rescaled_growths_fill_the_new_window() {
+    let from = Rect::from_w_h(300.0, 300.0);
+    let to = Rect::from_w_h(600.0, 150.0);
+    let config = Config::default();
+    let mut growths = create_new_growths(
+        from,
+        5,
+        &Palette::default(),
+        &mut ChaCha8Rng::seed_from_u64(8),
+    );
+    for _ in 0..30 {
+        step_growths_serial(&mut growths, Duration::from_millis(33), &config);
+    }
+    let before = growths.clone();
+
+    rescale_growths(&mut growths, from, to);
+
+    for (old, new) in before.iter().zip(&growths) {
+        assert_eq!(new.centre, vec2(old.centre.x * 2.0, old.centre.y / 2.0));
+        for (old_line, new_line) in old.lines.iter().zip(&new.lines) {
+            assert_eq!(new_line.end, rescale_point(old_line.end, from, to));
+            assert_eq!(new_line.points.len(), old_line.points.len());
+            let (old_tip, new_tip) = (
+                old_line.points.last().unwrap(),
+                new_line.points.last().unwrap(),
+            );
+            assert_eq!(
+                Point2::from(*new_tip),
+                rescale_point(Point2::from(*old_tip), from, to)
+            );
+            assert_eq!(new_tip.variation(), old_tip.variation());
+        }
+    }
+
+    // and they keep growing from where they were
+    step_growths_serial(&mut growths, Duration::from_millis(33), &config);
+}
+
+#[test]
+fn snapshots_restore_rescaled_to_the_current_world() {
+    let small = Rect::from_w_h(300.0, 300.0);
+    let mut simulation = Simulation::new(
+        4,
+        small,
+        Config::default(),
+        Palette::default(),
+        Timeline::default(),
+    )
+    .start();
+    for _ in 0..20 {
+        simulation.step(Duration::from_millis(16));
+    }
+    let snapshot = simulation.snapshot();
+
+    let big = Rect::from_w_h(600.0, 600.0);
+    let mut restored = Simulation::new(
+        9,
+        big,
+        Config::default(),
+        Palette::default(),
+        Timeline::default(),
+    );
+    restored.restore(snapshot.clone());
+
+    assert_eq!(restored.world, big);
+    assert_eq!(restored.snapshot().world_size, (600.0, 600.0));
+    for (before, after) in snapshot.growths.iter().zip(&restored.growths) {
+        assert!(after.centre.distance(before.centre * 2.0) < 1e-3);
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    animation::Curve,
+    mycelium::{
+        create_new_growths, render::render_snapshot, snapshot::Snapshot, step_growths_serial,
+        Config, WINDOW_SIZE,
+    },
+    palette::Palette,
+    raster::render_growths,
+    smoothing::{Smoothing, SmoothingMode},
+};
+use nannou::{geom::Rect, image};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const DT: Duration = Duration::from_millis(16);
+
+/// A snapshot of six growths a hundred steps in.
+fn snapshot() -> Snapshot {
+    let config = Config::default();
+    let world = Rect::from_w_h(WINDOW_SIZE, WINDOW_SIZE);
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut growths = create_new_growths(world, 6, &Palette::default(), &mut rng);
+    for _ in 0..100 {
+        step_growths_serial(&mut growths, DT, &config);
+    }
+
+    Snapshot {
+        seed: 7,
+        elapsed: DT * 100,
+        timeline_playhead: Duration::ZERO,
+        config,
+        rng,
+        growths,
+        world_size: (WINDOW_SIZE, WINDOW_SIZE),
+        vary_amount: 3.0,
+        vary_curve: Curve::Sine,
+        smoothing_mode: SmoothingMode::None,
+        palette: "liturgy".to_string(),
+        background: Palette::default().background,
+    }
+}
+
+#[test]
+fn restored_snapshot_carries_on_identically() {
+    let snapshot = snapshot();
+    let config = snapshot.config.clone();
+    let world = snapshot.world();
+    let mut growths = snapshot.growths.clone();
+
+    let path = std::env::temp_dir().join(format!("snapshot-{}.json", std::process::id()));
+    snapshot.save(&path).unwrap();
+    let restored = Snapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut restored_growths = restored.growths;
+    for _ in 0..100 {
+        step_growths_serial(&mut growths, DT, &config);
+        step_growths_serial(&mut restored_growths, DT, &restored.config);
+    }
+
+    let render = |growths| {
+        render_growths(
+            growths,
+            200,
+            200,
+            world,
+            Palette::default().background,
+            3.0,
+            &Smoothing::None,
+        )
+        .to_rgba8()
+    };
+    assert_eq!(render(&growths), render(&restored_growths));
+    assert_eq!(restored.rng, snapshot.rng);
+}
+
+#[test]
+fn snapshots_from_before_backgrounds_load() {
+    let mut json = serde_json::to_value(snapshot()).unwrap();
+    json.as_object_mut().unwrap().remove("background");
+    let restored: Snapshot = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.background, Palette::default().background);
+}
+
+#[test]
+fn renders_in_bands_match_rendering_all_at_once() {
+    let snapshot = snapshot();
+    // taller than a couple of bands, with a short one at the bottom
+    let (width, height) = (200, 1200);
+    let path = std::env::temp_dir().join(format!("render-{}.png", std::process::id()));
+    render_snapshot(&snapshot, width, Some(height), &path).unwrap();
+    let banded = image::open(&path).unwrap().to_rgba8();
+    std::fs::remove_file(&path).unwrap();
+
+    let whole = render_growths(
+        &snapshot.growths,
+        width,
+        height,
+        snapshot.world(),
+        snapshot.background,
+        snapshot.vary_amount,
+        &snapshot.config.smoothing(snapshot.smoothing_mode),
+    )
+    .to_rgba8();
+    assert_eq!(banded, whole);
+}
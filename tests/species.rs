@@ -0,0 +1,161 @@
+use std::{fs, path::Path, time::Duration};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{
+        create_new_colonies, create_new_growths,
+        growth::LineState,
+        placement::Placement,
+        simulation::Simulation,
+        species::{Species, Territory},
+        step_growths_serial,
+        timeline::Timeline,
+        topology::Topology,
+        Config,
+    },
+    palette::Palette,
+};
+use nannou::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const DT: Duration = Duration::from_millis(33);
+
+#[test]
+fn species_files_load_in_order() {
+    let species = Species::load_all(Path::new("species.example.txt")).unwrap();
+    let names: Vec<&str> = species.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["runner", "creeper"]);
+    assert_eq!(species[0].genes.step_amount, 1.6);
+    assert_eq!(species[0].genes.branching, 0.4);
+    assert_eq!(species[1].weight, 2.0);
+    // anything left out is 1
+    assert_eq!(species[1].genes.branching, 1.0);
+    assert!(species[1].colour.is_some());
+}
+
+#[test]
+fn bad_species_files_are_rejected() {
+    let path = std::env::temp_dir().join(format!("species-{}.txt", std::process::id()));
+    for contents in [
+        "step_amount = 2",
+        "[a]\nspeed = 2",
+        "[a]\nweight = 0",
+        "[a]\nbranching = -1",
+    ] {
+        fs::write(&path, contents).unwrap();
+        assert!(Species::load_all(&path).is_err(), "{contents}");
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn the_default_species_grows_like_before() {
+    let world = Rect::from_w_h(300.0, 300.0);
+    let palette = Palette::default();
+    let plain = create_new_growths(world, 6, &palette, &mut ChaCha8Rng::seed_from_u64(5));
+    let colonies = create_new_colonies(
+        world,
+        6,
+        &palette,
+        &[Species::default()],
+        &Placement::Uniform { padding: 50.0 },
+        &Topology::Complete,
+        &mut ChaCha8Rng::seed_from_u64(5),
+    );
+
+    for (a, b) in plain.iter().zip(&colonies) {
+        assert_eq!(a.centre, b.centre);
+        assert_eq!(a.colour, b.colour);
+        assert_eq!(a.lines.len(), b.lines.len());
+    }
+}
+
+#[test]
+fn growths_take_their_species_genes_and_colour() {
+    let species = Species::load_all(Path::new("species.example.txt")).unwrap();
+    let growths = create_new_colonies(
+        Rect::from_w_h(300.0, 300.0),
+        40,
+        &Palette::default(),
+        &species,
+        &Placement::Uniform { padding: 50.0 },
+        &Topology::Complete,
+        &mut ChaCha8Rng::seed_from_u64(9),
+    );
+
+    assert!(growths.iter().any(|g| g.species == 0));
+    assert!(growths.iter().any(|g| g.species == 1));
+    for growth in &growths {
+        let own = &species[growth.species];
+        assert_eq!(growth.genes, own.genes);
+        let colour: Srgb<u8> = Srgb::from_format(Srgb::new(
+            growth.colour.red,
+            growth.colour.green,
+            growth.colour.blue,
+        ));
+        assert_eq!(Some(colour), own.colour);
+    }
+    // runners only reach some of the others
+    let runner = growths.iter().find(|g| g.species == 0).unwrap();
+    assert!(runner.lines.len() < growths.len());
+}
+
+#[test]
+fn competing_species_go_dormant_in_each_others_territory() {
+    let species = Species::load_all(Path::new("species.example.txt")).unwrap();
+    let mut config = Config::default();
+    config.set("competition", 1.0);
+    let mut growths = create_new_colonies(
+        Rect::from_w_h(300.0, 300.0),
+        8,
+        &Palette::default(),
+        &species,
+        &Placement::Uniform { padding: 50.0 },
+        &Topology::Complete,
+        &mut ChaCha8Rng::seed_from_u64(3),
+    );
+    let mut territory = Territory::default();
+
+    for _ in 0..300 {
+        step_growths_serial(&mut growths, DT, &config);
+        territory.claim(&mut growths, &config);
+    }
+
+    let dormant = growths
+        .iter()
+        .flat_map(|g| &g.lines)
+        .filter(|l| l.state == LineState::Dormant)
+        .count();
+    assert!(dormant > 0);
+    assert!(territory.claimed(0) > 0 && territory.claimed(1) > 0);
+    for growth in &growths {
+        assert_eq!(
+            territory.owner(growth.centre, &config),
+            Some(growth.species)
+        );
+    }
+}
+
+#[test]
+fn growths_of_species_that_arent_loaded_take_palette_colours() {
+    let species = Species::load_all(Path::new("species.example.txt")).unwrap();
+    let mut simulation = Simulation::new(
+        3,
+        Rect::from_w_h(300.0, 300.0),
+        Config::default(),
+        Palette::default(),
+        Timeline::default(),
+    )
+    .with_species(species)
+    .start();
+    assert!(simulation.growths.iter().any(|g| g.species == 1));
+
+    // e.g. a snapshot from a session with more species than this one
+    simulation.species = vec![Species::default()];
+    let red = Palette::new("red", Srgb::new(0, 0, 0), &[(Srgb::new(255, 0, 0), 1.0)]).unwrap();
+    simulation.set_palette(red, &mut ChaCha8Rng::seed_from_u64(1));
+
+    for growth in &simulation.growths {
+        assert_eq!((growth.colour.red, growth.colour.green), (1.0, 0.0));
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    mycelium::{
+        sweep::{parse_seeds, Axis},
+        timeline::Timeline,
+    },
+    sheet::{contact_sheet, grid_sheet, LABEL_HEIGHT, PADDING, SHEET_BACKGROUND},
+};
+use nannou::image::{Rgba, RgbaImage};
+
+#[test]
+fn seeds_parse_from_ranges_and_lists() {
+    assert_eq!(parse_seeds("3..6").unwrap(), vec![3, 4, 5]);
+    assert_eq!(parse_seeds("3..=6").unwrap(), vec![3, 4, 5, 6]);
+    assert_eq!(parse_seeds("7, 1,42").unwrap(), vec![7, 1, 42]);
+    assert!(parse_seeds("6..3").is_err());
+    assert!(parse_seeds("a..b").is_err());
+}
+
+#[test]
+fn contact_sheets_tile_and_label_every_image() {
+    let tile = RgbaImage::from_pixel(20, 10, Rgba([255, 0, 0, 255]));
+    let tiles: Vec<_> = (0..5).map(|i| (tile.clone(), format!("#{i}"))).collect();
+    let sheet = contact_sheet(&tiles, 2);
+
+    let cell = (20 + PADDING, 10 + LABEL_HEIGHT + PADDING);
+    assert_eq!(
+        sheet.dimensions(),
+        (2 * cell.0 + PADDING, 3 * cell.1 + PADDING)
+    );
+    // the last tile is on its own in the third row
+    assert_eq!(
+        *sheet.get_pixel(PADDING, PADDING + 2 * cell.1),
+        tile[(0, 0)]
+    );
+    assert_eq!(
+        *sheet.get_pixel(PADDING + cell.0, PADDING + 2 * cell.1),
+        SHEET_BACKGROUND
+    );
+    // and its label is drawn underneath
+    let label_rows = PADDING + 2 * cell.1 + 10..PADDING + 2 * cell.1 + 10 + LABEL_HEIGHT;
+    assert!(label_rows
+        .flat_map(|y| (PADDING..PADDING + 20).map(move |x| (x, y)))
+        .any(|(x, y)| *sheet.get_pixel(x, y) != SHEET_BACKGROUND));
+}
+
+#[test]
+fn axes_spread_values_evenly_including_both_ends() {
+    let axis = Axis::parse("rand_factor=0.5..4", 3).unwrap();
+    assert_eq!(axis.key, "rand_factor");
+    assert_eq!(axis.values, vec![0.5, 2.25, 4.0]);
+    assert_eq!(axis.labels(), vec!["0.5", "2.25", "4"]);
+
+    assert_eq!(
+        Axis::parse("step_amount=8..12", 1).unwrap().values,
+        vec![8.0]
+    );
+    assert!(Axis::parse("step_amount=8", 3).is_err());
+    assert!(Axis::parse("step_amount=8..12", 0).is_err());
+}
+
+#[test]
+fn axes_only_sweep_config_keys() {
+    assert!(Axis::parse("step_ammount=8..12", 3).is_err());
+    assert!(Axis::parse(" step_amount =8..12", 3).is_ok());
+}
+
+#[test]
+fn axes_clash_with_timeline_tracks_for_their_key() {
+    let timeline = Timeline::load(Path::new("timeline.example.txt")).unwrap();
+    let swept = Axis::parse("rand_factor=0.5..4", 3).unwrap();
+    assert!(swept.check_timeline(&timeline).is_err());
+    let untouched = Axis::parse("step_amount=8..12", 3).unwrap();
+    assert!(untouched.check_timeline(&timeline).is_ok());
+}
+
+#[test]
+fn grid_sheets_have_a_cell_per_pair_of_values() {
+    let cell = RgbaImage::from_pixel(30, 20, Rgba([0, 255, 0, 255]));
+    let labels = |n: usize| (0..n).map(|i| i.to_string()).collect::<Vec<_>>();
+    let sheet = grid_sheet(&vec![cell; 6], "x", &labels(3), "y", &labels(2));
+
+    let (width, height) = sheet.dimensions();
+    assert!(width > 3 * (30 + PADDING) && height > 2 * (20 + PADDING));
+    // the bottom right cell sits in the corner
+    assert_eq!(
+        *sheet.get_pixel(width - PADDING - 1, height - PADDING - 1),
+        Rgba([0, 255, 0, 255])
+    );
+    let green = sheet
+        .pixels()
+        .filter(|p| **p == Rgba([0, 255, 0, 255]))
+        .count();
+    assert_eq!(green, 6 * 30 * 20);
+}
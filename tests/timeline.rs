@@ -0,0 +1,142 @@
+use std::{fs, path::Path, time::Duration};
+
+use liturgy_for_the_mushroom_at_the_end_of_the_world::{
+    animation::{ease_in_cubic, ease_in_out, ease_out_cubic, smoothstep},
+    mycelium::{
+        timeline::{Interpolation, Keyframe, Timeline},
+        Config,
+    },
+};
+
+fn keyframes(interpolation: Interpolation) -> [Keyframe; 2] {
+    [
+        Keyframe {
+            time: Duration::from_secs(10),
+            value: 2.0,
+            interpolation,
+        },
+        Keyframe {
+            time: Duration::from_secs(20),
+            value: 6.0,
+            interpolation: Interpolation::Linear,
+        },
+    ]
+}
+
+#[test]
+fn the_example_timeline_loads() {
+    let timeline = Timeline::load(Path::new("timeline.example.txt")).unwrap();
+    let keys: Vec<&str> = timeline.tracks().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["max_vary_amount", "rand_factor"]);
+    assert_eq!(timeline.duration(), Duration::from_secs(60));
+    assert!(timeline.is_playing());
+}
+
+#[test]
+fn bad_timelines_are_rejected() {
+    let path = std::env::temp_dir().join(format!("timeline-{}.txt", std::process::id()));
+    for contents in [
+        "rand_factor",
+        "rand_factor 0",
+        "rand_factor 0 1.5 linear extra",
+        "rand_factor soon 1.5",
+        "rand_factor -1 1.5",
+        "rand_factor inf 1.5",
+        "rand_factor 1e30 1.5",
+        "rand_factor NaN 1.5",
+        "rand_factor 0 lots",
+        "rand_factor 0 inf",
+        "rand_factor 0 1.5 bouncy",
+    ] {
+        fs::write(&path, contents).unwrap();
+        assert!(Timeline::load(&path).is_err(), "{contents}");
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn misspelled_keys_are_rejected_with_their_line() {
+    let path = std::env::temp_dir().join(format!("timeline-keys-{}.txt", std::process::id()));
+    fs::write(&path, "// comment\nrand_factor 0 1.5\nrand_facter 10 3\n").unwrap();
+    let error = Timeline::load(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+
+    assert!(error.to_string().contains("line 3"), "{error}");
+    assert!(error.to_string().contains("rand_facter"), "{error}");
+}
+
+#[test]
+fn keyframes_are_sorted_by_time() {
+    let path = std::env::temp_dir().join(format!("timeline-sorted-{}.txt", std::process::id()));
+    fs::write(&path, "step_amount 10 4\nstep_amount 0 8 // comment\n\n").unwrap();
+    let timeline = Timeline::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let (_, keyframes) = timeline.tracks().next().unwrap();
+    let values: Vec<f32> = keyframes.iter().map(|k| k.value).collect();
+    assert_eq!(values, [8.0, 4.0]);
+}
+
+#[test]
+fn values_hold_before_the_first_and_after_the_last_keyframe() {
+    let keyframes = keyframes(Interpolation::Linear);
+    assert_eq!(Timeline::value_at(&keyframes, Duration::ZERO), Some(2.0));
+    assert_eq!(
+        Timeline::value_at(&keyframes, Duration::from_secs(30)),
+        Some(6.0)
+    );
+    assert_eq!(Timeline::value_at(&[], Duration::ZERO), None);
+}
+
+#[test]
+fn each_interpolation_eases_between_keyframes() {
+    type Ease = fn(f32) -> f32;
+    let cases: [(Interpolation, Ease); 6] = [
+        (Interpolation::Step, |_| 0.0),
+        (Interpolation::Linear, |t| t),
+        (Interpolation::Smooth, smoothstep),
+        (Interpolation::EaseIn, ease_in_cubic),
+        (Interpolation::EaseOut, ease_out_cubic),
+        (Interpolation::EaseInOut, |t| ease_in_out(t, ease_in_cubic)),
+    ];
+
+    for (interpolation, curve) in cases {
+        let keyframes = keyframes(interpolation);
+        for t in [0.0, 0.25, 0.5, 0.75] {
+            let time = Duration::from_secs_f32(10.0 + 10.0 * t);
+            let expected = 2.0 + 4.0 * curve(t);
+            let value = Timeline::value_at(&keyframes, time).unwrap();
+            assert!(
+                (value - expected).abs() < 1e-4,
+                "{interpolation:?} at {t}: {value} != {expected}"
+            );
+        }
+        // every interpolation lands on the next keyframe
+        assert_eq!(
+            Timeline::value_at(&keyframes, Duration::from_secs(20)),
+            Some(6.0)
+        );
+    }
+}
+
+#[test]
+fn applying_sets_config_values_at_the_playhead() {
+    let mut timeline = Timeline::load(Path::new("timeline.example.txt")).unwrap();
+    let mut config = Config::default();
+    timeline.seek_to(Duration::from_secs(60));
+    timeline.apply(&mut config);
+    assert_eq!(config.get_with_default("rand_factor", 0.0), 6.0);
+}
+
+#[test]
+fn seeking_never_panics() {
+    let mut timeline = Timeline::default();
+    timeline.seek_by(5.0);
+    assert_eq!(timeline.playhead(), Duration::from_secs(5));
+    timeline.seek_by(-10.0);
+    assert_eq!(timeline.playhead(), Duration::ZERO);
+    timeline.seek_by(f32::INFINITY);
+    timeline.seek_by(f32::MAX);
+    timeline.seek_by(f32::NAN);
+    assert_eq!(timeline.playhead(), Duration::ZERO);
+}
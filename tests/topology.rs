@@ -0,0 +1,109 @@
+use liturgy_for_the_mushroom_at_the_end_of_the_world::mycelium::{
+    topology::{delaunay, gabriel, spanning_tree, Topology, TopologyMode},
+    Config,
+};
+use nannou::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn random_points(n: usize, seed: u64) -> Vec<Point2> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| vec2(rng.gen_range(-450.0..450.0), rng.gen_range(-450.0..450.0)))
+        .collect()
+}
+
+fn all_modes() -> Vec<TopologyMode> {
+    let mut modes = vec![TopologyMode::default()];
+    while modes.last().unwrap().next_mode() != modes[0] {
+        modes.push(modes.last().unwrap().next_mode());
+    }
+    modes
+}
+
+#[test]
+fn no_topology_links_a_growth_to_itself() {
+    let points = random_points(20, 1);
+    let config = Config::default();
+    for mode in all_modes() {
+        let links = config
+            .topology(mode)
+            .links(&points, &mut ChaCha8Rng::seed_from_u64(2));
+        assert_eq!(links.len(), points.len());
+        for (i, neighbours) in links.iter().enumerate() {
+            assert!(!neighbours.contains(&i), "{}", mode.name());
+        }
+    }
+}
+
+#[test]
+fn modes_parse_from_their_names() {
+    for mode in all_modes() {
+        assert_eq!(mode.name().parse::<TopologyMode>(), Ok(mode));
+    }
+    assert!("tree".parse::<TopologyMode>().is_err());
+}
+
+#[test]
+fn nearest_links_the_k_closest() {
+    let points = vec![
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(0.0, 2.0),
+        vec2(10.0, 10.0),
+    ];
+    let links = Topology::Nearest { k: 2 }.links(&points, &mut ChaCha8Rng::seed_from_u64(0));
+    assert_eq!(links[0], [1, 2]);
+    assert_eq!(links[3], [1, 2]);
+}
+
+#[test]
+fn delaunay_triangulates_a_square_around_a_point() {
+    let points = vec![
+        vec2(-1.0, -1.0),
+        vec2(1.0, -1.0),
+        vec2(1.1, 1.0),
+        vec2(-1.0, 1.0),
+        vec2(0.0, 0.0),
+    ];
+    let edges: Vec<_> = delaunay(&points).into_iter().collect();
+    assert_eq!(
+        edges,
+        [
+            (0, 1),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4)
+        ]
+    );
+}
+
+#[test]
+fn spanning_tree_within_gabriel_within_delaunay() {
+    for seed in 0..5 {
+        let points = random_points(30, seed);
+        let delaunay = delaunay(&points);
+        let gabriel = gabriel(&points);
+        let tree = spanning_tree(&points);
+
+        assert!(delaunay.len() <= 3 * points.len() - 6);
+        assert!(gabriel.is_subset(&delaunay));
+        assert!(tree.is_subset(&gabriel));
+        assert_eq!(tree.len(), points.len() - 1);
+    }
+}
+
+#[test]
+fn random_links_follow_the_probability() {
+    let points = random_points(10, 4);
+    let mut rng = ChaCha8Rng::seed_from_u64(4);
+    let none = Topology::Random { p: 0.0 }.links(&points, &mut rng);
+    assert!(none.iter().all(Vec::is_empty));
+
+    let all = Topology::Random { p: 1.0 }.links(&points, &mut rng);
+    assert_eq!(all, Topology::Complete.links(&points, &mut rng));
+}